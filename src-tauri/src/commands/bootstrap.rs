@@ -0,0 +1,468 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::{BufRead, BufReader, Read};
+use std::path::Path;
+use std::process::{Command, Stdio};
+use std::sync::Mutex;
+use tauri::{AppHandle, Emitter};
+
+use crate::config::{find_project, ProjectConfig, RepoConfig, TerminalPreset, REPO_CONFIG_FILE};
+use crate::store::ProjectRegistry;
+
+/// Event carrying the progress of every bootstrap run; the payload says which worktree
+pub const BOOTSTRAP_EVENT: &str = "worktree-bootstrap";
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct BootstrapEvent {
+    pub worktree_path: String,
+    pub kind: String, // "step_started", "output", "step_finished", "setup_untrusted" or "finished"
+    pub step: Option<String>,
+    /// A line of command output, or the message of a copy or symlink step
+    pub output: Option<String>,
+    pub success: Option<bool>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct BootstrapStepResult {
+    pub step: String,
+    pub success: bool,
+    pub output: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct BootstrapResult {
+    pub worktree_path: String,
+    pub success: bool,
+    pub steps: Vec<BootstrapStepResult>,
+    /// Setup commands that weren't run because the user hasn't approved them yet
+    pub untrusted_setup: Vec<String>,
+}
+
+/// Run the bootstrap pipeline for a worktree again, e.g. after fixing a failed install
+#[tauri::command]
+pub async fn bootstrap_worktree(
+    app: AppHandle,
+    project_path: String,
+    worktree_path: String,
+) -> Result<BootstrapResult, String> {
+    let config = ProjectConfig::load(&project_path)?;
+    tokio::task::spawn_blocking(move || {
+        run_bootstrap(&project_path, &worktree_path, &config, &|event| {
+            let _ = app.emit(BOOTSTRAP_EVENT, event);
+        })
+    })
+    .await
+    .map_err(|e| format!("Bootstrap task failed: {}", e))
+}
+
+/// Approve the `setup` commands of a project's `.manymany.toml`, so bootstrap runs them.
+///
+/// `commands` are the ones the user was shown; nothing is approved if the file changed since.
+#[tauri::command]
+pub async fn trust_setup_commands(project_path: String, commands: Vec<String>) -> Result<(), String> {
+    let setup = RepoConfig::load(&project_path)?.unwrap_or_default().worktree.setup;
+    if setup != commands {
+        return Err(format!(
+            "The setup commands in {} have changed. Review them again before trusting them.",
+            REPO_CONFIG_FILE
+        ));
+    }
+
+    let project_id = find_project(&project_path)
+        .ok_or_else(|| "Project not found".to_string())?
+        .id;
+    ProjectRegistry::update(|registry| {
+        let project = registry
+            .get_mut(&project_id)
+            .ok_or_else(|| "Project not found".to_string())?;
+        project.settings.trusted_setup = Some(commands.clone());
+        Ok(())
+    })
+}
+
+/// Approve the `[[terminals]]` and/or `[env]` of a project's `.manymany.toml`, whichever
+/// the user was shown.
+///
+/// Like `trust_setup_commands`, nothing is approved if the file changed since.
+#[tauri::command]
+pub async fn trust_repo_config(
+    project_path: String,
+    terminals: Option<Vec<TerminalPreset>>,
+    env: Option<BTreeMap<String, String>>,
+) -> Result<(), String> {
+    let repo = RepoConfig::load(&project_path)?.unwrap_or_default();
+    let changed = terminals.as_ref().is_some_and(|t| *t != repo.terminals)
+        || env.as_ref().is_some_and(|e| *e != repo.env);
+    if changed {
+        return Err(format!(
+            "The terminals or environment in {} have changed. Review them again before trusting them.",
+            REPO_CONFIG_FILE
+        ));
+    }
+
+    let project_id = find_project(&project_path)
+        .ok_or_else(|| "Project not found".to_string())?
+        .id;
+    ProjectRegistry::update(|registry| {
+        let project = registry
+            .get_mut(&project_id)
+            .ok_or_else(|| "Project not found".to_string())?;
+        if terminals.is_some() {
+            project.settings.trusted_terminals = terminals.clone();
+        }
+        if env.is_some() {
+            project.settings.trusted_env = env.clone();
+        }
+        Ok(())
+    })
+}
+
+/// Start the bootstrap pipeline in the background for a freshly created worktree.
+///
+/// Without `run_setup` the setup commands are only reported, for the user to start with
+/// `bootstrap_worktree`, e.g. when the worktree holds code fetched from someone else.
+pub(crate) fn spawn_bootstrap(app: &AppHandle, project_path: &str, worktree_path: &str, run_setup: bool) {
+    let mut config = match ProjectConfig::load(project_path) {
+        Ok(config) => config,
+        Err(e) => {
+            eprintln!("Skipping bootstrap of {}: {}", worktree_path, e);
+            return;
+        }
+    };
+    config.setup_trusted &= run_setup;
+    if config.copy.is_empty()
+        && config.symlink.is_empty()
+        && config.setup.is_empty()
+        && config.repo_config_error.is_none()
+    {
+        return;
+    }
+
+    let app = app.clone();
+    let project_path = project_path.to_string();
+    let worktree_path = worktree_path.to_string();
+    tokio::task::spawn_blocking(move || {
+        run_bootstrap(&project_path, &worktree_path, &config, &|event| {
+            let _ = app.emit(BOOTSTRAP_EVENT, event);
+        })
+    });
+}
+
+/// Copy and symlink the configured files from the main checkout, then run the setup commands.
+/// Setup commands the user hasn't trusted are reported instead of run.
+///
+/// File steps that fail don't stop the pipeline; a failed command does, since later
+/// commands usually depend on it, e.g. a build after an install.
+pub(crate) fn run_bootstrap(
+    project_path: &str,
+    worktree_path: &str,
+    config: &ProjectConfig,
+    emit: &(dyn Fn(BootstrapEvent) + Sync),
+) -> BootstrapResult {
+    let event = |kind: &str, step: Option<&str>, output: Option<String>, success: Option<bool>| {
+        emit(BootstrapEvent {
+            worktree_path: worktree_path.to_string(),
+            kind: kind.to_string(),
+            step: step.map(String::from),
+            output,
+            success,
+        })
+    };
+    let mut steps = Vec::new();
+
+    // Shown like a failed step, since nothing from the file is applied
+    if let Some(error) = &config.repo_config_error {
+        let step = format!("read {}", REPO_CONFIG_FILE);
+        event("step_started", Some(&step), None, None);
+        event("step_finished", Some(&step), Some(error.clone()), Some(false));
+        steps.push(BootstrapStepResult {
+            step,
+            success: false,
+            output: error.clone(),
+        });
+    }
+
+    let file_steps = config
+        .copy
+        .iter()
+        .map(|file| (file, false))
+        .chain(config.symlink.iter().map(|file| (file, true)));
+    for (file, link) in file_steps {
+        let step = format!("{} {}", if link { "symlink" } else { "copy" }, file);
+        event("step_started", Some(&step), None, None);
+
+        let source = Path::new(project_path).join(file);
+        let target = Path::new(worktree_path).join(file);
+        let result = if !source.exists() {
+            Ok(format!("{} not found in {}, skipped", file, project_path))
+        } else if link {
+            symlink_path(&source, &target).map(|_| format!("Linked {}", file))
+        } else {
+            copy_path(&source, &target).map(|_| format!("Copied {}", file))
+        };
+
+        let result = BootstrapStepResult {
+            step,
+            success: result.is_ok(),
+            output: result.unwrap_or_else(|e| e),
+        };
+        event("step_finished", Some(&result.step), Some(result.output.clone()), Some(result.success));
+        steps.push(result);
+    }
+
+    let untrusted_setup = if config.setup_trusted {
+        Vec::new()
+    } else {
+        config.setup.clone()
+    };
+    for command in &untrusted_setup {
+        event("setup_untrusted", Some(command), None, None);
+    }
+
+    for command in config.setup.iter().filter(|_| config.setup_trusted) {
+        event("step_started", Some(command), None, None);
+        let result = run_streamed(command, worktree_path, config, &|line| {
+            event("output", Some(command), Some(line), None);
+        });
+        let result = match result {
+            Ok((success, output)) => BootstrapStepResult { step: command.clone(), success, output },
+            Err(e) => BootstrapStepResult { step: command.clone(), success: false, output: e },
+        };
+        event("step_finished", Some(command), None, Some(result.success));
+
+        let failed = !result.success;
+        steps.push(result);
+        if failed {
+            break;
+        }
+    }
+
+    let success = steps.iter().all(|s| s.success);
+    event("finished", None, None, Some(success));
+
+    BootstrapResult {
+        worktree_path: worktree_path.to_string(),
+        success,
+        steps,
+        untrusted_setup,
+    }
+}
+
+/// Run `command` in the worktree, passing each output line to `on_line` as it arrives.
+/// Returns whether it succeeded and its combined output.
+fn run_streamed(
+    command: &str,
+    worktree_path: &str,
+    config: &ProjectConfig,
+    on_line: &(dyn Fn(String) + Sync),
+) -> Result<(bool, String), String> {
+    let mut child = shell_command(command)
+        .current_dir(worktree_path)
+        .envs(&config.env)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| format!("Failed to run command: {}", e))?;
+
+    let output = Mutex::new(String::new());
+    let read_lines = |pipe: Box<dyn Read + Send>| {
+        for line in BufReader::new(pipe).lines().map_while(Result::ok) {
+            let mut output = output.lock().unwrap();
+            output.push_str(&line);
+            output.push('\n');
+            drop(output);
+            on_line(line);
+        }
+    };
+
+    let stdout = child.stdout.take().map(|p| Box::new(p) as Box<dyn Read + Send>);
+    let stderr = child.stderr.take().map(|p| Box::new(p) as Box<dyn Read + Send>);
+    std::thread::scope(|scope| {
+        for pipe in [stdout, stderr].into_iter().flatten() {
+            scope.spawn(|| read_lines(pipe));
+        }
+    });
+
+    let status = child
+        .wait()
+        .map_err(|e| format!("Failed to run command: {}", e))?;
+    Ok((status.success(), output.into_inner().unwrap()))
+}
+
+fn shell_command(command: &str) -> Command {
+    if cfg!(windows) {
+        let mut cmd = Command::new("cmd");
+        cmd.args(&["/C", command]);
+        cmd
+    } else {
+        let mut cmd = Command::new("sh");
+        cmd.args(&["-c", command]);
+        cmd
+    }
+}
+
+/// Copy a file, or a directory recursively, creating parent directories as needed.
+/// Symlinks are copied as links rather than followed, so they can't pull in files from
+/// outside the checkout or loop forever.
+fn copy_path(source: &Path, target: &Path) -> Result<(), String> {
+    if let Some(parent) = target.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+    }
+
+    let metadata = fs::symlink_metadata(source)
+        .map_err(|e| format!("Failed to read {}: {}", source.display(), e))?;
+    if metadata.file_type().is_symlink() {
+        let link = fs::read_link(source)
+            .map_err(|e| format!("Failed to read {}: {}", source.display(), e))?;
+        symlink_path(&link, target)
+    } else if metadata.is_dir() {
+        fs::create_dir_all(target)
+            .map_err(|e| format!("Failed to create {}: {}", target.display(), e))?;
+        let entries = fs::read_dir(source)
+            .map_err(|e| format!("Failed to read {}: {}", source.display(), e))?;
+        for entry in entries.flatten() {
+            copy_path(&entry.path(), &target.join(entry.file_name()))?;
+        }
+        Ok(())
+    } else {
+        fs::copy(source, target)
+            .map(|_| ())
+            .map_err(|e| format!("Failed to copy {}: {}", source.display(), e))
+    }
+}
+
+/// Link `target` to `source`, so edits in either checkout show up in both
+fn symlink_path(source: &Path, target: &Path) -> Result<(), String> {
+    if let Some(parent) = target.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+    }
+
+    #[cfg(unix)]
+    let result = std::os::unix::fs::symlink(source, target);
+    #[cfg(windows)]
+    let result = if source.is_dir() {
+        std::os::windows::fs::symlink_dir(source, target)
+    } else {
+        std::os::windows::fs::symlink_file(source, target)
+    };
+
+    result.map_err(|e| format!("Failed to link {}: {}", target.display(), e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support;
+
+    fn bootstrap(project: &Path, worktree: &Path, config: &ProjectConfig) -> (BootstrapResult, Vec<BootstrapEvent>) {
+        let events = Mutex::new(Vec::new());
+        let result = run_bootstrap(
+            &project.to_string_lossy(),
+            &worktree.to_string_lossy(),
+            config,
+            &|event| events.lock().unwrap().push(event),
+        );
+        (result, events.into_inner().unwrap())
+    }
+
+    #[test]
+    fn test_run_bootstrap_copies_links_and_stops_at_failed_step() {
+        let root = test_support::temp_dir("bootstrap");
+        let project = root.join("project");
+        let worktree = root.join("worktree");
+        fs::create_dir_all(project.join("config")).unwrap();
+        fs::create_dir_all(project.join("node_modules")).unwrap();
+        fs::create_dir_all(&worktree).unwrap();
+        fs::write(project.join("config/.env"), "PORT=3000").unwrap();
+
+        let config = ProjectConfig {
+            copy: vec!["config/.env".to_string(), "missing.txt".to_string()],
+            symlink: vec!["node_modules".to_string()],
+            setup: vec![
+                "echo installing".to_string(),
+                "exit 3".to_string(),
+                "touch never".to_string(),
+            ],
+            setup_trusted: true,
+            ..Default::default()
+        };
+        let (result, events) = bootstrap(&project, &worktree, &config);
+
+        assert_eq!(fs::read_to_string(worktree.join("config/.env")).unwrap(), "PORT=3000");
+        assert!(fs::symlink_metadata(worktree.join("node_modules")).unwrap().file_type().is_symlink());
+        assert!(!worktree.join("never").exists());
+
+        let steps: Vec<_> = result.steps.iter().map(|s| (s.step.as_str(), s.success)).collect();
+        assert_eq!(
+            steps,
+            vec![
+                ("copy config/.env", true),
+                ("copy missing.txt", true),
+                ("symlink node_modules", true),
+                ("echo installing", true),
+                ("exit 3", false),
+            ]
+        );
+        assert!(result.steps[1].output.contains("skipped"));
+        assert!(!result.success);
+
+        assert!(events
+            .iter()
+            .any(|e| e.kind == "output" && e.output.as_deref() == Some("installing")));
+        let last = events.last().unwrap();
+        assert_eq!((last.kind.as_str(), last.success), ("finished", Some(false)));
+        assert_eq!(events.iter().filter(|e| e.kind == "step_started").count(), 5);
+
+        let _ = fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn test_run_bootstrap_only_reports_untrusted_setup() {
+        let root = test_support::temp_dir("bootstrap");
+        let config = ProjectConfig {
+            setup: vec!["touch ran".to_string()],
+            setup_trusted: false,
+            ..Default::default()
+        };
+        let (result, events) = bootstrap(&root, &root, &config);
+
+        assert!(!root.join("ran").exists());
+        assert!(result.success);
+        assert_eq!(result.untrusted_setup, vec!["touch ran"]);
+        assert!(events
+            .iter()
+            .any(|e| e.kind == "setup_untrusted" && e.step.as_deref() == Some("touch ran")));
+
+        let _ = fs::remove_dir_all(&root);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_copy_path_copies_symlinks_as_links() {
+        let root = test_support::temp_dir("bootstrap");
+        let source = root.join("project/config");
+        let outside = root.join("outside");
+        fs::create_dir_all(&source).unwrap();
+        fs::create_dir_all(&outside).unwrap();
+        fs::write(source.join("app.toml"), "port = 3000").unwrap();
+        fs::write(outside.join("secret"), "secret").unwrap();
+        std::os::unix::fs::symlink(&outside, source.join("outside")).unwrap();
+        std::os::unix::fs::symlink("..", source.join("loop")).unwrap();
+
+        let target = root.join("worktree/config");
+        copy_path(&source, &target).unwrap();
+
+        assert_eq!(fs::read_to_string(target.join("app.toml")).unwrap(), "port = 3000");
+        for (name, points_to) in [("outside", outside.as_path()), ("loop", Path::new(".."))] {
+            let link = target.join(name);
+            assert!(fs::symlink_metadata(&link).unwrap().file_type().is_symlink());
+            assert_eq!(fs::read_link(&link).unwrap(), points_to);
+        }
+
+        let _ = fs::remove_dir_all(&root);
+    }
+}
@@ -0,0 +1,331 @@
+use serde::{Deserialize, Serialize};
+use std::process::Command;
+
+/// A branch name with everything the branch picker shows about it
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct BranchInfo {
+    /// Name without the remote prefix, e.g. `feature/login`
+    pub name: String,
+    /// Remote to check the branch out from, when it exists on one
+    pub remote: Option<String>,
+    /// Every remote that has a branch with this name
+    pub remotes: Vec<String>,
+    pub location: String, // "local", "remote" or "both"
+    /// Upstream of the local branch, e.g. `origin/feature/login`
+    pub upstream: Option<String>,
+    /// The upstream was deleted on the remote and pruned locally
+    pub upstream_gone: bool,
+    /// Commits the local branch has that its upstream doesn't
+    pub ahead: u32,
+    /// Commits the upstream has that the local branch doesn't
+    pub behind: u32,
+    pub last_commit_date: String,
+    pub author: String,
+    pub subject: String,
+    /// Worktree that has the local branch checked out
+    pub worktree_path: Option<String>,
+}
+
+/// A remote-tracking branch split into its remote and branch name
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct RemoteBranch {
+    pub remote: String,
+    pub name: String,
+}
+
+impl RemoteBranch {
+    pub fn short_ref(&self) -> String {
+        format!("{}/{}", self.remote, self.name)
+    }
+}
+
+struct RefEntry {
+    refname: String,
+    upstream: String,
+    upstream_gone: bool,
+    ahead: u32,
+    behind: u32,
+    date: String,
+    author: String,
+    subject: String,
+    worktree_path: String,
+}
+
+/// All local and remote branches, one entry per branch name
+#[tauri::command]
+pub async fn list_branches(project_path: String) -> Result<Vec<BranchInfo>, String> {
+    read_branches(&project_path)
+}
+
+pub(crate) fn read_branches(project_path: &str) -> Result<Vec<BranchInfo>, String> {
+    let remotes = list_remotes(project_path)?;
+    let preferred = preferred_remote(project_path, &remotes);
+    let mut branches: Vec<BranchInfo> = Vec::new();
+
+    for entry in list_refs(project_path)? {
+        if let Some(name) = entry.refname.strip_prefix("refs/heads/") {
+            let info = BranchInfo {
+                name: name.to_string(),
+                remote: None,
+                remotes: Vec::new(),
+                location: "local".to_string(),
+                upstream: Some(entry.upstream).filter(|u| !u.is_empty()),
+                upstream_gone: entry.upstream_gone,
+                ahead: entry.ahead,
+                behind: entry.behind,
+                last_commit_date: entry.date,
+                author: entry.author,
+                subject: entry.subject,
+                worktree_path: Some(entry.worktree_path).filter(|p| !p.is_empty()),
+            };
+            // Local refs come first from for-each-ref, but don't rely on it
+            match branches.iter_mut().find(|b| b.name == name) {
+                Some(existing) => {
+                    let remotes = std::mem::take(&mut existing.remotes);
+                    *existing = info;
+                    existing.remotes = remotes;
+                }
+                None => branches.push(info),
+            }
+        } else if let Some(remote_branch) = split_remote_ref(&entry.refname, &remotes) {
+            // `origin/HEAD` is a pointer, not a branch
+            if remote_branch.name == "HEAD" {
+                continue;
+            }
+            match branches.iter_mut().find(|b| b.name == remote_branch.name) {
+                Some(existing) => existing.remotes.push(remote_branch.remote),
+                None => branches.push(BranchInfo {
+                    name: remote_branch.name,
+                    remote: None,
+                    remotes: vec![remote_branch.remote],
+                    location: "remote".to_string(),
+                    upstream: None,
+                    upstream_gone: false,
+                    ahead: 0,
+                    behind: 0,
+                    last_commit_date: entry.date,
+                    author: entry.author,
+                    subject: entry.subject,
+                    worktree_path: None,
+                }),
+            }
+        }
+    }
+
+    for branch in &mut branches {
+        let has_local = branch.location == "local";
+        if has_local && !branch.remotes.is_empty() {
+            branch.location = "both".to_string();
+        }
+        // The upstream's remote if the local branch has one, else the preferred remote
+        let upstream_remote = branch.upstream.as_ref().and_then(|u| {
+            branch.remotes.iter().find(|r| u.starts_with(&format!("{}/", r))).cloned()
+        });
+        branch.remote = upstream_remote.or_else(|| pick_remote(&branch.remotes, preferred.as_deref()));
+    }
+
+    branches.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(branches)
+}
+
+/// Resolve `branch` to a remote-tracking branch.
+///
+/// Accepts `<remote>/<name>` or a bare name that exists on a remote. A name on several
+/// remotes resolves to `checkout.defaultRemote`, then `origin`, and is an error otherwise.
+pub(crate) fn find_remote_branch(project_path: &str, branch: &str) -> Result<Option<RemoteBranch>, String> {
+    let remotes = list_remotes(project_path)?;
+    let remote_branches: Vec<RemoteBranch> = list_refs(project_path)?
+        .iter()
+        .filter_map(|entry| split_remote_ref(&entry.refname, &remotes))
+        .filter(|rb| rb.name != "HEAD")
+        .collect();
+
+    if let Some(exact) = remote_branches.iter().find(|rb| rb.short_ref() == branch) {
+        return Ok(Some(exact.clone()));
+    }
+
+    let candidates: Vec<&RemoteBranch> = remote_branches.iter().filter(|rb| rb.name == branch).collect();
+    if candidates.len() <= 1 {
+        return Ok(candidates.first().map(|rb| (*rb).clone()));
+    }
+
+    let names: Vec<String> = candidates.iter().map(|rb| rb.remote.clone()).collect();
+    match pick_remote(&names, preferred_remote(project_path, &remotes).as_deref()) {
+        Some(remote) => Ok(candidates.into_iter().find(|rb| rb.remote == remote).cloned()),
+        None => Err(format!(
+            "Branch '{}' exists on several remotes ({}).\n\nPlease choose one, e.g. '{}/{}'.",
+            branch,
+            names.join(", "),
+            names[0],
+            branch
+        )),
+    }
+}
+
+/// `refs/remotes/<remote>/<name>`, where both parts may contain slashes
+fn split_remote_ref(refname: &str, remotes: &[String]) -> Option<RemoteBranch> {
+    let rest = refname.strip_prefix("refs/remotes/")?;
+    remotes
+        .iter()
+        .filter(|remote| rest.starts_with(&format!("{}/", remote)))
+        .max_by_key(|remote| remote.len())
+        .map(|remote| RemoteBranch {
+            remote: remote.clone(),
+            name: rest[remote.len() + 1..].to_string(),
+        })
+}
+
+fn pick_remote(remotes: &[String], preferred: Option<&str>) -> Option<String> {
+    if remotes.len() == 1 {
+        return remotes.first().cloned();
+    }
+    preferred
+        .into_iter()
+        .chain(std::iter::once("origin"))
+        .find(|p| remotes.iter().any(|r| r == p))
+        .map(String::from)
+}
+
+/// `checkout.defaultRemote`, which git also uses to pick between remotes with the same branch
+fn preferred_remote(project_path: &str, remotes: &[String]) -> Option<String> {
+    let output = Command::new("git")
+        .args(&["-C", project_path, "config", "checkout.defaultRemote"])
+        .output()
+        .ok()?;
+    let remote = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    Some(remote).filter(|r| output.status.success() && remotes.contains(r))
+}
+
+fn list_remotes(project_path: &str) -> Result<Vec<String>, String> {
+    let output = Command::new("git")
+        .args(&["-C", project_path, "remote"])
+        .output()
+        .map_err(|e| format!("Failed to list remotes: {}", e))?;
+
+    Ok(String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .map(|l| l.trim().to_string())
+        .filter(|l| !l.is_empty())
+        .collect())
+}
+
+fn list_refs(project_path: &str) -> Result<Vec<RefEntry>, String> {
+    let output = Command::new("git")
+        .args(&[
+            "-C",
+            project_path,
+            "for-each-ref",
+            "--format=%(refname)%00%(upstream:short)%00%(upstream:track)%00%(committerdate:iso-strict)%00%(authorname)%00%(subject)%00%(worktreepath)",
+            "refs/heads",
+            "refs/remotes",
+        ])
+        .output()
+        .map_err(|e| format!("Failed to list branches: {}", e))?;
+
+    if !output.status.success() {
+        return Err(format!(
+            "Failed to list branches: {}",
+            String::from_utf8_lossy(&output.stderr)
+        ));
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.split('\0');
+            let refname = fields.next()?.to_string();
+            let upstream = fields.next()?.to_string();
+            let track = fields.next()?;
+            let (ahead, behind) = parse_track(track);
+            Some(RefEntry {
+                refname,
+                upstream,
+                upstream_gone: track == "[gone]",
+                ahead,
+                behind,
+                date: fields.next()?.to_string(),
+                author: fields.next()?.to_string(),
+                subject: fields.next()?.to_string(),
+                worktree_path: fields.next().unwrap_or("").to_string(),
+            })
+        })
+        .collect())
+}
+
+/// Ahead and behind counts from `%(upstream:track)`, e.g. `[ahead 1, behind 2]`
+fn parse_track(track: &str) -> (u32, u32) {
+    let mut counts = (0, 0);
+    for part in track.trim_start_matches('[').trim_end_matches(']').split(", ") {
+        if let Some(n) = part.strip_prefix("ahead ") {
+            counts.0 = n.parse().unwrap_or(0);
+        } else if let Some(n) = part.strip_prefix("behind ") {
+            counts.1 = n.parse().unwrap_or(0);
+        }
+    }
+    counts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{self, commit_file, git as run};
+
+    #[test]
+    fn test_split_remote_ref_prefers_longest_remote() {
+        let remotes = vec!["origin".to_string(), "team".to_string(), "team/fork".to_string()];
+        assert_eq!(
+            split_remote_ref("refs/remotes/team/fork/feature/x", &remotes),
+            Some(RemoteBranch { remote: "team/fork".to_string(), name: "feature/x".to_string() })
+        );
+        assert_eq!(split_remote_ref("refs/remotes/other/x", &remotes), None);
+        assert_eq!(split_remote_ref("refs/heads/x", &remotes), None);
+    }
+
+    #[test]
+    fn test_read_branches_from_a_repo_with_two_remotes() {
+        let root = test_support::temp_dir("branches");
+        let repo = test_support::init_repo(&root);
+        run(&root, &["clone", "-q", "--bare", "origin.git", "fork.git"]);
+        run(&repo, &["remote", "add", "fork", "../fork.git"]);
+
+        // Behind and ahead of origin/main at once
+        commit_file(&repo, "a.txt", "pushed", "pushed");
+        run(&repo, &["push", "-q", "origin", "main"]);
+        run(&repo, &["reset", "-q", "--hard", "HEAD~1"]);
+        commit_file(&repo, "a.txt", "local", "local");
+
+        run(&repo, &["push", "-q", "fork", "main:fork-only"]);
+        run(&repo, &["branch", "local-only"]);
+        run(&repo, &["worktree", "add", "-q", "../wt", "local-only"]);
+        run(&repo, &["fetch", "-q", "--all"]);
+
+        let branches = read_branches(&repo.to_string_lossy()).unwrap();
+        let branch = |name: &str| branches.iter().find(|b| b.name == name).unwrap();
+        let names: Vec<&str> = branches.iter().map(|b| b.name.as_str()).collect();
+        assert_eq!(names, vec!["fork-only", "local-only", "main"]);
+
+        let main = branch("main");
+        assert_eq!(main.location, "both");
+        assert_eq!(main.upstream.as_deref(), Some("origin/main"));
+        assert_eq!(main.remote.as_deref(), Some("origin"));
+        assert_eq!(main.remotes, vec!["fork", "origin"]);
+        assert_eq!((main.ahead, main.behind), (1, 1));
+        assert_eq!(main.subject, "local");
+
+        let fork_only = branch("fork-only");
+        assert_eq!(fork_only.location, "remote");
+        assert_eq!(fork_only.remote.as_deref(), Some("fork"));
+        assert_eq!(fork_only.worktree_path, None);
+
+        let local_only = branch("local-only");
+        assert_eq!(local_only.location, "local");
+        assert_eq!(local_only.upstream, None);
+        assert_eq!((local_only.ahead, local_only.behind), (0, 0));
+        assert_eq!(
+            local_only.worktree_path.as_deref().map(crate::store::normalize_path),
+            Some(crate::store::normalize_path(&root.join("wt").to_string_lossy()))
+        );
+
+        let _ = std::fs::remove_dir_all(&root);
+    }
+}
@@ -0,0 +1,379 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+use chrono::Utc;
+use tauri::AppHandle;
+
+use crate::commands::bootstrap::spawn_bootstrap;
+use crate::commands::project::{add_project, AddProjectRequest, Project, ProjectSettings};
+use crate::commands::worktree::{add_worktree, list_worktrees, CreateWorktreeOptions};
+use crate::config::{validate_env, TerminalPreset};
+use crate::git_commands::branch_exists;
+use crate::layout::validate_template;
+use crate::store::{normalize_path, ProjectRegistry, WorktreeStore};
+
+/// Current format of exported bundles
+pub const BUNDLE_VERSION: u32 = 1;
+
+/// A portable description of a project and its worktrees.
+///
+/// Machine-specific data (ids, absolute paths, the worktree root) is left out.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ProjectBundle {
+    pub version: u32,
+    pub exported_at: String,
+    pub project: BundledProject,
+    #[serde(default)]
+    pub worktrees: Vec<BundledWorktree>,
+    #[serde(default)]
+    pub terminals: Option<Vec<TerminalPreset>>,
+    #[serde(default)]
+    pub env: BTreeMap<String, String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BundledProject {
+    pub name: String,
+    pub project_type: String,
+    #[serde(default)]
+    pub default_branch: Option<String>,
+    #[serde(default)]
+    pub path_template: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BundledWorktree {
+    pub name: String,
+    pub branch: String,
+    #[serde(default)]
+    pub base_ref: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ImportedWorktree {
+    pub name: String,
+    pub branch: String,
+    pub path: Option<String>,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ImportProjectResult {
+    pub project: Project,
+    pub worktrees: Vec<ImportedWorktree>,
+    /// Branches from the bundle that exist neither locally nor on a remote
+    pub missing_branches: Vec<String>,
+}
+
+/// Write a project's definition to `output_path`, as TOML for a `.toml` file and JSON otherwise
+#[tauri::command]
+pub async fn export_project(project_id: String, output_path: String) -> Result<ProjectBundle, String> {
+    let project = ProjectRegistry::load()?
+        .get(&project_id)
+        .cloned()
+        .ok_or_else(|| "Project not found".to_string())?;
+
+    let mut worktrees = Vec::new();
+    if project.project_type == "repository" {
+        let project_root = normalize_path(&project.path);
+        for worktree in list_worktrees(project.path.clone(), Some(project.id.clone())).await? {
+            // The main checkout comes with the clone
+            if worktree.is_missing || worktree.branch.is_empty() || normalize_path(&worktree.path) == project_root {
+                continue;
+            }
+
+            let name = worktree.display_name.clone().unwrap_or_else(|| {
+                Path::new(&worktree.path)
+                    .file_name()
+                    .map(|n| n.to_string_lossy().to_string())
+                    .unwrap_or_else(|| worktree.branch.clone())
+            });
+            worktrees.push(BundledWorktree {
+                name,
+                branch: worktree.branch,
+                base_ref: worktree.base_ref,
+            });
+        }
+    }
+
+    let bundle = ProjectBundle {
+        version: BUNDLE_VERSION,
+        exported_at: Utc::now().to_rfc3339(),
+        project: BundledProject {
+            name: project.name,
+            project_type: project.project_type,
+            default_branch: project.default_branch,
+            path_template: project.settings.path_template,
+        },
+        worktrees,
+        terminals: project.settings.terminals,
+        env: project.settings.env,
+    };
+
+    let content = serialize_bundle(&bundle, &output_path)?;
+    fs::write(&output_path, content).map_err(|e| format!("Failed to write bundle: {}", e))?;
+
+    Ok(bundle)
+}
+
+/// Register the project from a bundle at `local_path` and recreate its worktrees there
+#[tauri::command]
+pub async fn import_project(
+    app: AppHandle,
+    bundle_path: String,
+    local_path: String,
+    name: Option<String>,
+) -> Result<ImportProjectResult, String> {
+    let result = import_bundle(&bundle_path, &local_path, name).await?;
+    for path in result.worktrees.iter().filter_map(|w| w.path.as_ref()) {
+        spawn_bootstrap(&app, &local_path, path, true);
+    }
+    Ok(result)
+}
+
+/// Everything `import_project` does except bootstrapping the new worktrees
+async fn import_bundle(
+    bundle_path: &str,
+    local_path: &str,
+    name: Option<String>,
+) -> Result<ImportProjectResult, String> {
+    let content = fs::read_to_string(bundle_path)
+        .map_err(|e| format!("Failed to read bundle: {}", e))?;
+    let bundle = parse_bundle(&content, bundle_path)?;
+
+    if bundle.version > BUNDLE_VERSION {
+        return Err(format!(
+            "Bundle was written by a newer version of ManyMany (version {}, expected {})",
+            bundle.version, BUNDLE_VERSION
+        ));
+    }
+
+    if let Some(template) = &bundle.project.path_template {
+        validate_template(template)?;
+    }
+    validate_env(&bundle.env)?;
+
+    let mut project = add_project(AddProjectRequest {
+        name: name.unwrap_or(bundle.project.name),
+        path: local_path.to_string(),
+        project_type: bundle.project.project_type,
+        default_branch: bundle.project.default_branch,
+        workspace_repos: None,
+    })
+    .await?;
+
+    project.settings = ProjectSettings {
+        worktree_root: None,
+        path_template: bundle.project.path_template,
+        env: bundle.env,
+        terminals: bundle.terminals,
+        protected_branches: None,
+        trusted_setup: None,
+        trusted_terminals: None,
+        trusted_env: None,
+    };
+    ProjectRegistry::update(|registry| {
+        if let Some(entry) = registry.get_mut(&project.id) {
+            entry.settings = project.settings.clone();
+        }
+        Ok(())
+    })?;
+
+    let mut worktrees = Vec::new();
+    let mut missing_branches = Vec::new();
+
+    for worktree in bundle.worktrees {
+        if !branch_exists(local_path, &worktree.branch) {
+            missing_branches.push(worktree.branch.clone());
+            worktrees.push(ImportedWorktree {
+                name: worktree.name,
+                branch: worktree.branch,
+                path: None,
+                error: Some("Branch not found locally or on any remote".to_string()),
+            });
+            continue;
+        }
+
+        // Seeding caches copies whole directories, so keep it off the async runtime
+        let (path, branch, project_id, worktree_name) = (
+            local_path.to_string(),
+            worktree.branch.clone(),
+            project.id.clone(),
+            worktree.name.clone(),
+        );
+        let created = tokio::task::spawn_blocking(move || {
+            add_worktree(&path, &branch, project_id, worktree_name, &CreateWorktreeOptions::default())
+        })
+        .await
+        .map_err(|e| format!("Failed to create worktree: {}", e))?;
+
+        match created {
+            Ok(created) => {
+                // Keep the original base ref rather than the branch we checked out
+                if worktree.base_ref.is_some() {
+                    let result = WorktreeStore::update(&project.id, |store| {
+                        if let Some(record) = store.records_mut().iter_mut().find(|r| r.id == created.id) {
+                            record.base_ref = worktree.base_ref.clone();
+                        }
+                        Ok(())
+                    });
+                    if let Err(e) = result {
+                        eprintln!("Failed to record base ref for {}: {}", created.path, e);
+                    }
+                }
+                worktrees.push(ImportedWorktree {
+                    name: worktree.name,
+                    branch: worktree.branch,
+                    path: Some(created.path),
+                    error: None,
+                });
+            }
+            Err(e) => worktrees.push(ImportedWorktree {
+                name: worktree.name,
+                branch: worktree.branch,
+                path: None,
+                error: Some(e),
+            }),
+        }
+    }
+
+    Ok(ImportProjectResult {
+        project,
+        worktrees,
+        missing_branches,
+    })
+}
+
+fn serialize_bundle(bundle: &ProjectBundle, path: &str) -> Result<String, String> {
+    if is_toml(path) {
+        toml::to_string_pretty(bundle).map_err(|e| format!("Failed to serialize bundle: {}", e))
+    } else {
+        serde_json::to_string_pretty(bundle).map_err(|e| format!("Failed to serialize bundle: {}", e))
+    }
+}
+
+fn parse_bundle(content: &str, path: &str) -> Result<ProjectBundle, String> {
+    if is_toml(path) {
+        toml::from_str(content).map_err(|e| format!("Failed to parse bundle: {}", e))
+    } else {
+        serde_json::from_str(content).map_err(|e| format!("Failed to parse bundle: {}", e))
+    }
+}
+
+fn is_toml(path: &str) -> bool {
+    path.to_lowercase().ends_with(".toml")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support;
+
+    #[test]
+    fn test_bundle_round_trips_through_toml_and_json() {
+        let mut env = BTreeMap::new();
+        env.insert("PORT".to_string(), "3000".to_string());
+        let bundle = ProjectBundle {
+            version: BUNDLE_VERSION,
+            exported_at: "2026-01-01T00:00:00+00:00".to_string(),
+            project: BundledProject {
+                name: "api".to_string(),
+                project_type: "repository".to_string(),
+                default_branch: Some("main".to_string()),
+                path_template: Some("{root}/{project}/{branch}".to_string()),
+            },
+            worktrees: vec![
+                BundledWorktree {
+                    name: "login".to_string(),
+                    branch: "feature/login".to_string(),
+                    base_ref: Some("main".to_string()),
+                },
+                BundledWorktree {
+                    name: "docs".to_string(),
+                    branch: "docs".to_string(),
+                    base_ref: None,
+                },
+            ],
+            terminals: Some(vec![TerminalPreset {
+                name: "Claude".to_string(),
+                command: "claude".to_string(),
+            }]),
+            env,
+        };
+
+        for path in ["bundle.toml", "bundle.json"] {
+            let parsed = parse_bundle(&serialize_bundle(&bundle, path).unwrap(), path).unwrap();
+            assert_eq!(parsed.project.name, "api");
+            assert_eq!(parsed.project.path_template, bundle.project.path_template);
+            assert_eq!(parsed.worktrees.len(), 2);
+            assert_eq!(parsed.worktrees[0].branch, "feature/login");
+            assert_eq!(parsed.worktrees[0].base_ref.as_deref(), Some("main"));
+            assert_eq!(parsed.worktrees[1].base_ref, None);
+            assert_eq!(parsed.terminals, bundle.terminals);
+            assert_eq!(parsed.env["PORT"], "3000");
+        }
+    }
+
+    #[tokio::test]
+    async fn test_import_recreates_worktrees_and_settings() {
+        let env = test_support::data_dir();
+        let repo = test_support::init_repo(&env.root);
+        test_support::git(&repo, &["branch", "feature"]);
+        let local_path = repo.to_string_lossy().to_string();
+
+        let mut vars = BTreeMap::new();
+        vars.insert("PORT".to_string(), "3000".to_string());
+        let terminals = Some(vec![TerminalPreset {
+            name: "Server".to_string(),
+            command: "npm run dev".to_string(),
+        }]);
+        let bundle = ProjectBundle {
+            version: BUNDLE_VERSION,
+            exported_at: "2026-01-01T00:00:00+00:00".to_string(),
+            project: BundledProject {
+                name: "api".to_string(),
+                project_type: "repository".to_string(),
+                default_branch: Some("main".to_string()),
+                path_template: None,
+            },
+            worktrees: vec![
+                BundledWorktree {
+                    name: "feature".to_string(),
+                    branch: "feature".to_string(),
+                    base_ref: Some("main".to_string()),
+                },
+                BundledWorktree {
+                    name: "gone".to_string(),
+                    branch: "gone".to_string(),
+                    base_ref: None,
+                },
+            ],
+            terminals: terminals.clone(),
+            env: vars,
+        };
+        let bundle_path = env.root.join("bundle.json").to_string_lossy().to_string();
+        fs::write(&bundle_path, serialize_bundle(&bundle, &bundle_path).unwrap()).unwrap();
+
+        let result = import_bundle(&bundle_path, &local_path, Some("imported".to_string()))
+            .await
+            .unwrap();
+        assert_eq!(result.project.name, "imported");
+        assert_eq!(result.missing_branches, vec!["gone".to_string()]);
+        assert_eq!(result.worktrees.len(), 2);
+        assert!(result.worktrees[1].path.is_none());
+
+        let created = result.worktrees[0].path.clone().expect("feature should be created");
+        assert_eq!(test_support::git(Path::new(&created), &["branch", "--show-current"]), "feature");
+
+        let registered = ProjectRegistry::load().unwrap().get(&result.project.id).cloned().unwrap();
+        assert_eq!(registered.settings.terminals, terminals);
+        assert_eq!(registered.settings.env["PORT"], "3000");
+
+        let records = list_worktrees(local_path.clone(), Some(result.project.id.clone())).await.unwrap();
+        let record = records.iter().find(|w| w.branch == "feature").unwrap();
+        assert_eq!(record.base_ref.as_deref(), Some("main"));
+
+        let _ = fs::remove_dir_all(&env.root);
+    }
+}
@@ -0,0 +1,180 @@
+use serde::{Deserialize, Serialize};
+
+use crate::commands::branches::read_branches;
+use crate::commands::project::get_default_branch;
+use crate::commands::removal::{removal_check, RemovalCheck, RemoveWorktreeOptions};
+use crate::commands::usage::is_merged;
+use crate::commands::worktree::{git_worktree_list, remove_worktree};
+use crate::store::{ProjectRegistry, WorktreeStore};
+
+/// A worktree whose branch looks finished
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CleanupCandidate {
+    pub path: String,
+    pub branch: String,
+    pub reason: String, // "merged" or "upstream_gone"
+    /// What removing it would lose; unsafe worktrees are skipped by cleanup_worktrees
+    pub removal: RemovalCheck,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CleanupResult {
+    pub path: String,
+    pub success: bool,
+    pub deleted_branch: Option<String>,
+    pub error: Option<String>,
+}
+
+/// Worktrees whose branch is merged into the default branch or whose upstream was deleted
+#[tauri::command]
+pub async fn find_cleanup_candidates(project_id: String) -> Result<Vec<CleanupCandidate>, String> {
+    let project_path = project_path(&project_id)?;
+
+    tokio::task::spawn_blocking(move || {
+        let default_branch = get_default_branch(project_path.clone())?;
+        let branches = read_branches(&project_path)?;
+        let records = WorktreeStore::load(&project_id)?;
+        let mut candidates = Vec::new();
+
+        // The first entry is the main checkout, which is never a candidate
+        for entry in git_worktree_list(&project_path)?.into_iter().skip(1) {
+            // Detached and locked worktrees are left alone
+            if entry.is_bare || entry.branch.is_empty() || entry.locked.is_some() || entry.branch == default_branch {
+                continue;
+            }
+
+            let base_commit = records.find_by_path(&entry.path).and_then(|r| r.base_commit.as_deref());
+            let reason = if is_merged(&project_path, &entry.branch, &default_branch, base_commit) {
+                "merged"
+            } else if branches.iter().any(|b| b.name == entry.branch && b.upstream_gone) {
+                "upstream_gone"
+            } else {
+                continue;
+            };
+
+            candidates.push(CleanupCandidate {
+                removal: removal_check(&entry.path)?,
+                path: entry.path,
+                branch: entry.branch,
+                reason: reason.to_string(),
+            });
+        }
+
+        Ok(candidates)
+    })
+    .await
+    .map_err(|e| format!("Failed to find cleanup candidates: {}", e))?
+}
+
+/// Remove the selected worktrees one after another, optionally deleting their branches.
+///
+/// Worktrees with work that would be lost are refused, like a single unconfirmed removal.
+#[tauri::command]
+pub async fn cleanup_worktrees(
+    project_id: String,
+    worktree_paths: Vec<String>,
+    delete_branches: Option<bool>,
+) -> Result<Vec<CleanupResult>, String> {
+    let project_path = project_path(&project_id)?;
+    let options = RemoveWorktreeOptions {
+        delete_branch: delete_branches.unwrap_or(false),
+        ..Default::default()
+    };
+
+    let mut results = Vec::new();
+    // One at a time; git locks the repository while it updates worktree metadata
+    for path in worktree_paths {
+        let result = remove_worktree(
+            project_path.clone(),
+            path.clone(),
+            Some(project_id.clone()),
+            Some(options.clone()),
+        )
+        .await;
+
+        results.push(match result {
+            Ok(removed) => CleanupResult {
+                path,
+                success: true,
+                deleted_branch: removed.deleted_branch,
+                error: None,
+            },
+            Err(e) => CleanupResult {
+                path,
+                success: false,
+                deleted_branch: None,
+                error: Some(e),
+            },
+        });
+    }
+
+    Ok(results)
+}
+
+fn project_path(project_id: &str) -> Result<String, String> {
+    ProjectRegistry::load()?
+        .get(project_id)
+        .map(|p| p.path.clone())
+        .ok_or_else(|| "Project not found".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::worktree::{add_worktree, CreateWorktreeOptions};
+    use crate::test_support::{self, commit_file, git};
+    use std::fs;
+    use std::path::Path;
+
+    #[tokio::test]
+    async fn test_find_cleanup_candidates() {
+        let env = test_support::data_dir();
+        let repo = test_support::init_repo(&env.root);
+        let project_id = test_support::add_project(&repo).await;
+        let project_path = repo.to_string_lossy().to_string();
+        let options = CreateWorktreeOptions {
+            create_branch: true,
+            ..Default::default()
+        };
+        let add = |branch: &str| {
+            let worktree = add_worktree(&project_path, branch, project_id.clone(), branch.to_string(), &options).unwrap();
+            Path::new(&worktree.path).to_path_buf()
+        };
+
+        // Created from main and never worked on, while main moved ahead
+        add("fresh");
+        // Worked on and merged into main
+        let merged = add("merged");
+        commit_file(&merged, "merged.txt", "merged", "merged work");
+        // Made outside the app, so only the reflog knows where it started
+        let outside = env.root.join("outside");
+        git(&repo, &["worktree", "add", "-q", "-b", "outside", &outside.to_string_lossy()]);
+        commit_file(&outside, "outside.txt", "outside", "outside work");
+        // Pushed, then deleted on the remote without being merged
+        let gone = add("gone");
+        commit_file(&gone, "gone.txt", "gone", "abandoned work");
+        git(&gone, &["push", "-q", "-u", "origin", "gone"]);
+        git(&repo, &["push", "-q", "origin", "--delete", "gone"]);
+
+        git(&repo, &["merge", "-q", "--no-ff", "-m", "merge", "merged", "outside"]);
+        commit_file(&repo, "main.txt", "main", "main moves on");
+
+        let mut candidates: Vec<_> = find_cleanup_candidates(project_id.clone())
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|c| (c.branch, c.reason))
+            .collect();
+        candidates.sort();
+        assert_eq!(
+            candidates,
+            vec![
+                ("gone".to_string(), "upstream_gone".to_string()),
+                ("merged".to_string(), "merged".to_string()),
+                ("outside".to_string(), "merged".to_string()),
+            ]
+        );
+
+        let _ = fs::remove_dir_all(&env.root);
+    }
+}
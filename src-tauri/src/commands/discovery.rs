@@ -0,0 +1,197 @@
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::commands::project::{add_project, get_default_branch, AddProjectRequest, Project};
+use crate::git_commands::{detect_repository, is_linked_worktree};
+use crate::store::ProjectRegistry;
+
+const DEFAULT_MAX_DEPTH: usize = 3;
+
+/// Directories that never contain repositories worth registering
+const SKIPPED_DIRS: &[&str] = &["node_modules", "target", "vendor", "dist", "build", "__pycache__"];
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DiscoveredProject {
+    pub name: String,
+    pub path: String,
+    pub project_type: String, // "repository" or "workspace"
+    pub is_bare: bool,
+    pub default_branch: Option<String>,
+    pub is_registered: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AddProjectResult {
+    pub path: String,
+    pub project: Option<Project>,
+    pub error: Option<String>,
+}
+
+/// Find git repositories, bare repositories and `.code-workspace` files under `root`
+#[tauri::command]
+pub async fn discover_projects(
+    root: String,
+    max_depth: Option<usize>,
+) -> Result<Vec<DiscoveredProject>, String> {
+    let root = PathBuf::from(&root);
+    if !root.is_dir() {
+        return Err("Selected folder does not exist".to_string());
+    }
+
+    let registry = ProjectRegistry::load()?;
+    // Walking a big folder asks git about every repository, so keep it off the async runtime
+    tokio::task::spawn_blocking(move || {
+        let mut found = Vec::new();
+        walk(&root, 0, max_depth.unwrap_or(DEFAULT_MAX_DEPTH), &registry, &mut found);
+
+        found.sort_by(|a, b| a.path.cmp(&b.path));
+        found
+    })
+    .await
+    .map_err(|e| format!("Failed to discover projects: {}", e))
+}
+
+fn walk(
+    dir: &Path,
+    depth: usize,
+    max_depth: usize,
+    registry: &ProjectRegistry,
+    found: &mut Vec<DiscoveredProject>,
+) {
+    let is_repository = looks_like_repository(dir);
+    // Worktrees belong to the repository they were added to, which is listed on its own
+    if is_repository && !is_linked_worktree(dir) {
+        if let Some(info) = detect_repository(dir) {
+            let path = dir.to_string_lossy().to_string();
+            found.push(DiscoveredProject {
+                name: display_name(dir),
+                default_branch: get_default_branch(path.clone()).ok(),
+                is_registered: registry.find_by_path(&path).is_some(),
+                path,
+                project_type: "repository".to_string(),
+                is_bare: info.is_bare,
+            });
+        }
+    }
+
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(_) => return,
+    };
+
+    for entry in entries.flatten() {
+        // Don't follow symlinks, they can loop back up the tree
+        let file_type = match entry.file_type() {
+            Ok(file_type) => file_type,
+            Err(_) => continue,
+        };
+        let path = entry.path();
+        let name = entry.file_name().to_string_lossy().to_string();
+
+        if file_type.is_file() && name.ends_with(".code-workspace") {
+            let path_str = path.to_string_lossy().to_string();
+            found.push(DiscoveredProject {
+                name: name.trim_end_matches(".code-workspace").to_string(),
+                is_registered: registry.find_by_path(&path_str).is_some(),
+                path: path_str,
+                project_type: "workspace".to_string(),
+                is_bare: false,
+                default_branch: None,
+            });
+        } else if file_type.is_dir()
+            && !is_repository
+            && depth < max_depth
+            && !name.starts_with('.')
+            && !SKIPPED_DIRS.contains(&name.as_str())
+        {
+            walk(&path, depth + 1, max_depth, registry, found);
+        }
+    }
+}
+
+/// Cheap filesystem check before asking git: a `.git` entry, or the layout of a bare repo
+fn looks_like_repository(dir: &Path) -> bool {
+    dir.join(".git").exists()
+        || (dir.join("HEAD").is_file() && dir.join("objects").is_dir() && dir.join("refs").is_dir())
+}
+
+fn display_name(dir: &Path) -> String {
+    let name = dir
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_else(|| dir.to_string_lossy().to_string());
+    name.strip_suffix(".git")
+        .filter(|n| !n.is_empty())
+        .map(String::from)
+        .unwrap_or(name)
+}
+
+/// Register several projects at once, reporting success or failure for each
+#[tauri::command]
+pub async fn add_projects(requests: Vec<AddProjectRequest>) -> Result<Vec<AddProjectResult>, String> {
+    let mut results = Vec::new();
+
+    for request in requests {
+        let path = request.path.clone();
+        match add_project(request).await {
+            Ok(project) => results.push(AddProjectResult {
+                path,
+                project: Some(project),
+                error: None,
+            }),
+            Err(e) => results.push(AddProjectResult {
+                path,
+                project: None,
+                error: Some(e),
+            }),
+        }
+    }
+
+    Ok(results)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{self, git};
+
+    #[tokio::test]
+    async fn test_discover_projects_skips_worktrees() {
+        let env = test_support::data_dir();
+        let root = env.root.join("code");
+        fs::create_dir_all(root.join("nested")).unwrap();
+        fs::create_dir_all(root.join("node_modules")).unwrap();
+
+        // A checkout with a linked worktree next to it
+        git(&root, &["init", "-q", "-b", "main", "app"]);
+        git(
+            &root.join("app"),
+            &["-c", "user.name=Test", "-c", "user.email=test@example.com", "commit", "-q", "--allow-empty", "-m", "initial"],
+        );
+        git(&root.join("app"), &["worktree", "add", "-q", "../app-feature"]);
+        // A bare repository with a worktree of its own
+        git(&root, &["clone", "-q", "--bare", "app", "bare.git"]);
+        git(&root.join("bare.git"), &["worktree", "add", "-q", "../bare-main", "main"]);
+        git(&root, &["init", "-q", "nested/lib"]);
+        git(&root, &["init", "-q", "node_modules/pkg"]);
+        fs::write(root.join("team.code-workspace"), "{}").unwrap();
+
+        let found = discover_projects(root.to_string_lossy().to_string(), None).await.unwrap();
+        let found: Vec<_> = found
+            .iter()
+            .map(|p| (p.path.strip_prefix(&*root.to_string_lossy()).unwrap(), p.project_type.as_str(), p.is_bare))
+            .collect();
+        assert_eq!(
+            found,
+            vec![
+                ("/app", "repository", false),
+                ("/bare.git", "repository", true),
+                ("/nested/lib", "repository", false),
+                ("/team.code-workspace", "workspace", false),
+            ]
+        );
+
+        let _ = fs::remove_dir_all(&env.root);
+    }
+}
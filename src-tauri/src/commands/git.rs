@@ -19,9 +19,13 @@ pub struct GitFile {
 
 #[tauri::command]
 pub async fn get_git_status(worktree_path: String) -> Result<GitStatus, String> {
+    read_git_status(&worktree_path)
+}
+
+pub(crate) fn read_git_status(worktree_path: &str) -> Result<GitStatus, String> {
     // Get current branch
     let branch_output = Command::new("git")
-        .args(&["-C", &worktree_path, "branch", "--show-current"])
+        .args(&["-C", worktree_path, "branch", "--show-current"])
         .output()
         .map_err(|e| format!("Failed to get branch: {}", e))?;
     
@@ -33,7 +37,7 @@ pub async fn get_git_status(worktree_path: String) -> Result<GitStatus, String>
     
     // Get status
     let status_output = Command::new("git")
-        .args(&["-C", &worktree_path, "status", "--porcelain=v1"])
+        .args(&["-C", worktree_path, "status", "--porcelain=v1"])
         .output()
         .map_err(|e| format!("Failed to get status: {}", e))?;
     
@@ -77,7 +81,7 @@ pub async fn get_git_status(worktree_path: String) -> Result<GitStatus, String>
     }
     
     // Get ahead/behind count
-    let (ahead, behind) = get_ahead_behind(&worktree_path, &branch);
+    let (ahead, behind) = get_ahead_behind(worktree_path, &branch);
     
     Ok(GitStatus {
         branch,
@@ -91,8 +95,12 @@ pub async fn get_git_status(worktree_path: String) -> Result<GitStatus, String>
 
 #[tauri::command]
 pub async fn git_commit(worktree_path: String, message: String) -> Result<(), String> {
+    commit(&worktree_path, &message)
+}
+
+pub(crate) fn commit(worktree_path: &str, message: &str) -> Result<(), String> {
     let output = Command::new("git")
-        .args(&["-C", &worktree_path, "commit", "-m", &message])
+        .args(&["-C", worktree_path, "commit", "-m", message])
         .output()
         .map_err(|e| format!("Failed to commit: {}", e))?;
     
@@ -106,8 +114,12 @@ pub async fn git_commit(worktree_path: String, message: String) -> Result<(), St
 
 #[tauri::command]
 pub async fn git_stage_file(worktree_path: String, file_path: String) -> Result<(), String> {
+    stage_file(&worktree_path, &file_path)
+}
+
+pub(crate) fn stage_file(worktree_path: &str, file_path: &str) -> Result<(), String> {
     let output = Command::new("git")
-        .args(&["-C", &worktree_path, "add", &file_path])
+        .args(&["-C", worktree_path, "add", file_path])
         .output()
         .map_err(|e| format!("Failed to stage file: {}", e))?;
     
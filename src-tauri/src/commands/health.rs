@@ -0,0 +1,343 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use crate::commands::worktree::git_worktree_list;
+use crate::git_commands::{containing_repository, local_branch_exists};
+use crate::layout::WorktreeLayout;
+use crate::store::{normalize_path, ProjectRegistry};
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct HealthIssue {
+    pub kind: String, // "missing_repository", "prunable_worktree", "orphan_directory" or "deleted_branch"
+    pub path: String,
+    pub branch: Option<String>,
+    pub detail: String,
+    /// Command that fixes this issue, if there is one
+    pub fix: Option<String>, // "prune_worktrees", "relink_worktree" or "delete_orphan_directory"
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ProjectHealthReport {
+    pub project_id: String,
+    pub project_path: String,
+    pub healthy: bool,
+    pub issues: Vec<HealthIssue>,
+}
+
+#[tauri::command]
+pub async fn check_project_health(project_id: String) -> Result<ProjectHealthReport, String> {
+    let registry = ProjectRegistry::load()?;
+    let project = registry
+        .get(&project_id)
+        .ok_or_else(|| "Project not found".to_string())?;
+
+    let mut issues = Vec::new();
+
+    if !Path::new(&project.path).exists() {
+        issues.push(HealthIssue {
+            kind: "missing_repository".to_string(),
+            path: project.path.clone(),
+            branch: None,
+            detail: "The project path no longer exists. Move it back or remove the project.".to_string(),
+            fix: None,
+        });
+    } else if project.project_type == "repository" {
+        check_repository(&project.path, &mut issues)?;
+    }
+
+    Ok(ProjectHealthReport {
+        project_id,
+        project_path: project.path.clone(),
+        healthy: issues.is_empty(),
+        issues,
+    })
+}
+
+fn check_repository(project_path: &str, issues: &mut Vec<HealthIssue>) -> Result<(), String> {
+    let worktrees = git_worktree_list(project_path)?;
+
+    for worktree in &worktrees {
+        if let Some(reason) = &worktree.prunable {
+            issues.push(HealthIssue {
+                kind: "prunable_worktree".to_string(),
+                path: worktree.path.clone(),
+                branch: Some(worktree.branch.clone()).filter(|b| !b.is_empty()),
+                detail: if reason.is_empty() {
+                    "Git marks this worktree as prunable".to_string()
+                } else {
+                    format!("Git marks this worktree as prunable: {}", reason)
+                },
+                fix: Some("prune_worktrees".to_string()),
+            });
+            continue;
+        }
+
+        if !worktree.branch.is_empty() && !local_branch_exists(project_path, &worktree.branch) {
+            issues.push(HealthIssue {
+                kind: "deleted_branch".to_string(),
+                path: worktree.path.clone(),
+                branch: Some(worktree.branch.clone()),
+                detail: format!(
+                    "Branch '{}' was deleted but this worktree still has it checked out",
+                    worktree.branch
+                ),
+                fix: None,
+            });
+        }
+    }
+
+    // Orphans can only be found when all worktrees share one parent directory
+    let worktree_dir = WorktreeLayout::for_project(project_path)?.project_dir(project_path);
+    if let Some(Ok(entries)) = worktree_dir.map(fs::read_dir) {
+        // Projects with the same folder name share this directory under the default layout
+        let mut known = registered_worktrees();
+        known.extend(worktrees.iter().map(|w| normalize_path(&w.path)));
+
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if !path.is_dir() || known.contains(&normalize_path(&path.to_string_lossy())) {
+                continue;
+            }
+
+            // A `.git` file means this was a worktree that got moved or renamed by hand
+            let (detail, fix) = if path.join(".git").is_file() {
+                (
+                    "No git worktree points to this directory, but it looks like a moved worktree",
+                    Some("relink_worktree"),
+                )
+            } else if is_repository(&path) {
+                ("No registered worktree points to this directory, but it holds a git repository", None)
+            } else {
+                ("No git worktree points to this directory", Some("delete_orphan_directory"))
+            };
+
+            issues.push(HealthIssue {
+                kind: "orphan_directory".to_string(),
+                path: path.to_string_lossy().to_string(),
+                branch: None,
+                detail: detail.to_string(),
+                fix: fix.map(str::to_string),
+            });
+        }
+    }
+
+    Ok(())
+}
+
+/// Worktrees of every registered repository project
+fn registered_worktrees() -> HashSet<PathBuf> {
+    let registry = match ProjectRegistry::load() {
+        Ok(registry) => registry,
+        Err(_) => return HashSet::new(),
+    };
+
+    registry
+        .projects()
+        .iter()
+        .filter(|p| p.project_type == "repository" && Path::new(&p.path).exists())
+        .filter_map(|p| git_worktree_list(&p.path).ok())
+        .flatten()
+        .map(|w| normalize_path(&w.path))
+        .collect()
+}
+
+/// Whether `path` is, or is inside, a git repository or worktree
+fn is_repository(path: &Path) -> bool {
+    path.join(".git").exists() || containing_repository(path).is_some()
+}
+
+/// Remove administrative data for worktrees whose directories are gone
+#[tauri::command]
+pub async fn prune_worktrees(project_path: String) -> Result<Vec<String>, String> {
+    let output = Command::new("git")
+        .args(&["-C", &project_path, "worktree", "prune", "--verbose"])
+        .output()
+        .map_err(|e| format!("Failed to prune worktrees: {}", e))?;
+
+    if !output.status.success() {
+        let error = String::from_utf8_lossy(&output.stderr);
+        return Err(format!("Failed to prune worktrees: {}", error));
+    }
+
+    // `--verbose` reports each removal on stderr
+    let pruned = String::from_utf8_lossy(&output.stderr)
+        .lines()
+        .chain(String::from_utf8_lossy(&output.stdout).lines())
+        .map(|line| line.trim().to_string())
+        .filter(|line| !line.is_empty())
+        .collect();
+
+    Ok(pruned)
+}
+
+/// Re-link a worktree that was moved without `git worktree move`
+#[tauri::command]
+pub async fn relink_worktree(project_path: String, worktree_path: String) -> Result<(), String> {
+    let output = Command::new("git")
+        .args(&["-C", &project_path, "worktree", "repair", &worktree_path])
+        .output()
+        .map_err(|e| format!("Failed to repair worktree: {}", e))?;
+
+    if !output.status.success() {
+        let error = String::from_utf8_lossy(&output.stderr);
+        return Err(format!("Failed to repair worktree: {}", error));
+    }
+
+    Ok(())
+}
+
+/// Delete a directory under the project's worktree folder that no worktree points to
+#[tauri::command]
+pub async fn delete_orphan_directory(project_id: String, path: String) -> Result<(), String> {
+    let registry = ProjectRegistry::load()?;
+    let project = registry
+        .get(&project_id)
+        .ok_or_else(|| "Project not found".to_string())?;
+
+    let worktree_dir = WorktreeLayout::for_project(&project.path)?
+        .project_dir(&project.path)
+        .ok_or_else(|| "This project's path template has no shared worktree directory".to_string())?;
+    let worktree_dir = normalize_path(&worktree_dir.to_string_lossy());
+    let target = normalize_path(&path);
+
+    if target.parent() != Some(worktree_dir.as_path()) {
+        return Err(format!(
+            "Refusing to delete {}: it is not inside {}",
+            path,
+            worktree_dir.display()
+        ));
+    }
+
+    // The directory may be shared with projects of the same name
+    let mut worktrees = registered_worktrees();
+    worktrees.extend(git_worktree_list(&project.path)?.iter().map(|w| normalize_path(&w.path)));
+    if worktrees.contains(&target) {
+        return Err(format!("Refusing to delete {}: it is an active worktree", path));
+    }
+    if is_repository(&target) {
+        return Err(format!(
+            "Refusing to delete {}: it holds a git repository or worktree",
+            path
+        ));
+    }
+
+    fs::remove_dir_all(&target)
+        .map_err(|e| format!("Failed to delete directory: {}", e))?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{self, git};
+
+    #[tokio::test]
+    async fn test_check_project_health_finds_broken_worktrees() {
+        let env = test_support::data_dir();
+        let repo = test_support::init_repo(&env.root);
+        let project_id = test_support::add_project(&repo).await;
+        let project_path = repo.to_string_lossy().to_string();
+
+        let report = check_project_health(project_id.clone()).await.unwrap();
+        assert!(report.healthy);
+
+        let layout = WorktreeLayout::for_project(&project_path).unwrap();
+        let add = |name: &str| {
+            let path = layout.worktree_path(&project_path, name, name).unwrap();
+            git(&repo, &["worktree", "add", "-q", "-b", name, &path.to_string_lossy()]);
+            path
+        };
+
+        // Removed by hand instead of with `git worktree remove`
+        let gone = add("gone");
+        fs::remove_dir_all(&gone).unwrap();
+        // The branch was deleted from under its worktree
+        let doomed = add("doomed");
+        git(&repo, &["update-ref", "-d", "refs/heads/doomed"]);
+        // Left behind in the worktree folder with no worktree pointing at it
+        let stray = layout.project_dir(&project_path).unwrap().join("stray");
+        fs::create_dir_all(&stray).unwrap();
+
+        let report = check_project_health(project_id).await.unwrap();
+        assert!(!report.healthy);
+        let issue = |kind: &str| {
+            report
+                .issues
+                .iter()
+                .find(|i| i.kind == kind)
+                .unwrap_or_else(|| panic!("no {} issue in {:?}", kind, report.issues))
+        };
+
+        let prunable = issue("prunable_worktree");
+        assert_eq!(normalize_path(&prunable.path), normalize_path(&gone.to_string_lossy()));
+        assert_eq!(prunable.fix.as_deref(), Some("prune_worktrees"));
+
+        let deleted = issue("deleted_branch");
+        assert_eq!(normalize_path(&deleted.path), normalize_path(&doomed.to_string_lossy()));
+        assert_eq!(deleted.branch.as_deref(), Some("doomed"));
+
+        let orphan = issue("orphan_directory");
+        assert_eq!(normalize_path(&orphan.path), normalize_path(&stray.to_string_lossy()));
+        assert_eq!(orphan.fix.as_deref(), Some("delete_orphan_directory"));
+        assert_eq!(report.issues.len(), 3);
+
+        let _ = fs::remove_dir_all(&env.root);
+    }
+
+    #[tokio::test]
+    async fn test_orphans_skip_worktrees_of_projects_with_the_same_name() {
+        let env = test_support::data_dir();
+        for dir in ["a", "b"] {
+            fs::create_dir_all(env.root.join(dir)).unwrap();
+        }
+        // Both are called "repo", so they share `{root}/repo` under the default layout
+        let repo_a = test_support::init_repo(&env.root.join("a"));
+        let repo_b = test_support::init_repo(&env.root.join("b"));
+        let project_a = test_support::add_project(&repo_a).await;
+        test_support::add_project(&repo_b).await;
+        let path_a = repo_a.to_string_lossy().to_string();
+
+        let layout = WorktreeLayout::for_project(&path_a).unwrap();
+        let other = layout.worktree_path(&repo_b.to_string_lossy(), "other", "other").unwrap();
+        git(&repo_b, &["worktree", "add", "-q", "-b", "other", &other.to_string_lossy()]);
+        assert_eq!(layout.project_dir(&path_a), other.parent().map(Path::to_path_buf));
+
+        let moved = layout.worktree_path(&path_a, "moved", "moved").unwrap();
+        git(&repo_a, &["worktree", "add", "-q", "-b", "moved", &moved.to_string_lossy()]);
+        let renamed = moved.with_file_name("renamed");
+        fs::rename(&moved, &renamed).unwrap();
+        let clone = moved.with_file_name("clone");
+        git(&env.root, &["init", "-q", &clone.to_string_lossy()]);
+        let stray = moved.with_file_name("stray");
+        fs::create_dir_all(&stray).unwrap();
+
+        let report = check_project_health(project_a.clone()).await.unwrap();
+        let orphan = |path: &Path| {
+            let path = normalize_path(&path.to_string_lossy());
+            report
+                .issues
+                .iter()
+                .find(|i| i.kind == "orphan_directory" && normalize_path(&i.path) == path)
+        };
+        assert!(orphan(&other).is_none());
+        assert_eq!(orphan(&renamed).unwrap().fix.as_deref(), Some("relink_worktree"));
+        assert_eq!(orphan(&clone).unwrap().fix, None);
+        assert_eq!(orphan(&stray).unwrap().fix.as_deref(), Some("delete_orphan_directory"));
+
+        let delete = |path: &Path| {
+            delete_orphan_directory(project_a.clone(), path.to_string_lossy().to_string())
+        };
+        assert!(delete(&other).await.unwrap_err().contains("active worktree"));
+        assert!(delete(&renamed).await.is_err());
+        assert!(delete(&clone).await.is_err());
+        assert!(other.exists() && renamed.exists() && clone.exists());
+        delete(&stray).await.unwrap();
+        assert!(!stray.exists());
+
+        let _ = fs::remove_dir_all(&env.root);
+    }
+}
@@ -0,0 +1,194 @@
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+use std::sync::Mutex;
+use tauri::State;
+
+use crate::commands::worktree::resolve_project_id;
+use crate::git_commands::git;
+use crate::store::{normalize_path, WorktreeStore};
+use crate::terminal::TerminalManager;
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct MoveWorktreeResult {
+    pub path: String,
+    /// Terminals whose working directory was inside the worktree
+    pub relocated_terminals: Vec<String>,
+}
+
+/// Lock a worktree so git won't prune it, e.g. while it lives on removable storage
+#[tauri::command]
+pub async fn lock_worktree(
+    project_path: String,
+    worktree_path: String,
+    reason: Option<String>,
+) -> Result<(), String> {
+    let mut args = vec!["worktree", "lock"];
+    let reason = reason.filter(|r| !r.trim().is_empty());
+    if let Some(reason) = &reason {
+        args.push("--reason");
+        args.push(reason);
+    }
+    args.push(&worktree_path);
+
+    git(&project_path, &args).map_err(|e| format!("Failed to lock worktree: {}", e))?;
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn unlock_worktree(project_path: String, worktree_path: String) -> Result<(), String> {
+    git(&project_path, &["worktree", "unlock", &worktree_path])
+        .map_err(|e| format!("Failed to unlock worktree: {}", e))?;
+    Ok(())
+}
+
+/// Move a worktree to `new_path`, keeping its metadata and pointing open terminals at the new place
+#[tauri::command]
+pub async fn move_worktree(
+    project_path: String,
+    worktree_path: String,
+    new_path: String,
+    project_id: Option<String>,
+    state: State<'_, Mutex<TerminalManager>>,
+) -> Result<MoveWorktreeResult, String> {
+    // Resolved while the worktree is still there to follow symlinks
+    let old_path = normalize_path(&Path::new(&project_path).join(&worktree_path).to_string_lossy());
+    let planned = state
+        .lock()
+        .unwrap()
+        .plan_relocation(&old_path.to_string_lossy());
+
+    let new_path = relocate_worktree(&project_path, &worktree_path, &new_path, project_id)?;
+
+    let relocated_terminals = {
+        let mut manager = state.lock().unwrap();
+        manager.apply_relocation(planned, &new_path)
+    };
+
+    Ok(MoveWorktreeResult {
+        path: new_path,
+        relocated_terminals,
+    })
+}
+
+/// Move a worktree and its metadata, returning the resolved new path.
+///
+/// Relative paths are taken relative to the project, like git does with `-C`.
+pub(crate) fn relocate_worktree(
+    project_path: &str,
+    worktree_path: &str,
+    new_path: &str,
+    project_id: Option<String>,
+) -> Result<String, String> {
+    let worktree_path = Path::new(project_path).join(worktree_path);
+    let target = Path::new(project_path).join(new_path);
+    if target.exists() {
+        return Err(format!(
+            "Target already exists: {}\n\nPlease choose a different location.",
+            new_path
+        ));
+    }
+    if let Some(parent) = target.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create worktree directory: {}", e))?;
+    }
+
+    // The record has to be found before the move, while its path can still be resolved
+    let project_id = resolve_project_id(project_path, project_id);
+    let record_id = project_id
+        .as_ref()
+        .and_then(|id| WorktreeStore::load(id).ok())
+        .and_then(|store| store.find_by_path(&worktree_path.to_string_lossy()).map(|r| r.id.clone()));
+
+    // git refuses to move locked worktrees and the main worktree, which is what we want
+    git(
+        project_path,
+        &["worktree", "move", &worktree_path.to_string_lossy(), &target.to_string_lossy()],
+    )
+    .map_err(|e| format!("Failed to move worktree: {}", e))?;
+    let new_path = normalize_path(&target.to_string_lossy()).to_string_lossy().to_string();
+
+    if let (Some(id), Some(record_id)) = (project_id, record_id) {
+        // The worktree has moved; a later list_worktrees matches the record by git_name if this fails
+        if let Err(e) = WorktreeStore::update(&id, |store| {
+            for record in store.records_mut().iter_mut().filter(|r| r.id == record_id) {
+                record.path = new_path.clone();
+            }
+            Ok(())
+        }) {
+            eprintln!("Failed to update worktree metadata: {}", e);
+        }
+    }
+
+    Ok(new_path)
+}
+
+/// Fix the links between the repository and its worktrees after they were moved outside git.
+///
+/// `worktree_paths` are the new locations of worktrees moved by hand; git finds the rest itself.
+#[tauri::command]
+pub async fn repair_worktrees(
+    project_path: String,
+    worktree_paths: Option<Vec<String>>,
+) -> Result<Vec<String>, String> {
+    let mut args = vec!["-C", project_path.as_str(), "worktree", "repair"];
+    let paths = worktree_paths.unwrap_or_default();
+    args.extend(paths.iter().map(String::as_str));
+
+    let output = Command::new("git")
+        .args(&args)
+        .output()
+        .map_err(|e| format!("Failed to repair worktrees: {}", e))?;
+
+    // git reports each repair on stderr, along with any errors
+    let messages: Vec<String> = String::from_utf8_lossy(&output.stderr)
+        .lines()
+        .map(|l| l.trim().to_string())
+        .filter(|l| !l.is_empty())
+        .collect();
+
+    if !output.status.success() {
+        return Err(format!("Failed to repair worktrees: {}", messages.join("\n")));
+    }
+    Ok(messages)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::worktree::{add_worktree, CreateWorktreeOptions};
+    use crate::test_support;
+    use std::path::PathBuf;
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_relocate_worktree_stores_the_resolved_path() {
+        let env = test_support::data_dir();
+        let repo = test_support::init_repo(&env.root);
+        let project_id = test_support::add_project(&repo).await;
+        let project_path = repo.to_string_lossy().to_string();
+        let options = CreateWorktreeOptions {
+            create_branch: true,
+            ..Default::default()
+        };
+        let worktree =
+            add_worktree(&project_path, "feature", project_id.clone(), "feature".to_string(), &options)
+                .unwrap();
+
+        // Named through a symlink, which no longer resolves once the worktree has moved
+        let link = env.root.join("link");
+        std::os::unix::fs::symlink(Path::new(&worktree.path).parent().unwrap(), &link).unwrap();
+        let old_path = link.join("feature").to_string_lossy().to_string();
+
+        let new_path =
+            relocate_worktree(&project_path, &old_path, "../moved", Some(project_id.clone())).unwrap();
+
+        let expected = normalize_path(&env.root.join("moved").to_string_lossy());
+        assert_eq!(PathBuf::from(&new_path), expected);
+        let store = WorktreeStore::load(&project_id).unwrap();
+        assert_eq!(store.find_by_path(&new_path).map(|r| r.path.as_str()), Some(new_path.as_str()));
+
+        let _ = fs::remove_dir_all(&env.root);
+    }
+}
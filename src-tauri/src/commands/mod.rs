@@ -1,4 +1,19 @@
 pub mod project;
 pub mod worktree;
 pub mod git;
-pub mod terminal;
\ No newline at end of file
+pub mod terminal;
+pub mod health;
+pub mod settings;
+pub mod discovery;
+pub mod bundle;
+pub mod workspace;
+pub mod branches;
+pub mod removal;
+pub mod lifecycle;
+pub mod bootstrap;
+pub mod seed;
+pub mod usage;
+pub mod cleanup;
+pub mod sync;
+pub mod sparse;
+pub mod review;
\ No newline at end of file
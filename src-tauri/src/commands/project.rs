@@ -1,11 +1,17 @@
 use serde::{Deserialize, Serialize};
-use std::path::PathBuf;
-use std::fs;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
 use std::process::Command;
 use uuid::Uuid;
 use chrono::Utc;
 
-#[derive(Debug, Serialize, Deserialize)]
+use crate::config::{ProjectConfig, TerminalPreset};
+use crate::git_commands::{detect_repository, git};
+use crate::manifest::{load_manifest, ManifestFormat};
+use crate::store::ProjectRegistry;
+use crate::workspace::{WorkspaceExtensions, WorkspaceFile, WorkspaceLaunch};
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct Project {
     pub id: String,
     pub name: String,
@@ -13,10 +19,46 @@ pub struct Project {
     pub project_type: String, // "repository" or "workspace"
     pub default_branch: Option<String>,
     pub created_at: String,
+    #[serde(default)]
     pub worktrees: Vec<Worktree>,
+    /// Repository's common git dir, as reported by `git rev-parse --git-common-dir`
+    #[serde(default)]
+    pub git_common_dir: Option<String>,
+    #[serde(default)]
+    pub is_bare: bool,
+    #[serde(default)]
+    pub settings: ProjectSettings,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+/// Per-project overrides of the user-level `AppSettings`
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct ProjectSettings {
+    #[serde(default)]
+    pub worktree_root: Option<String>,
+    #[serde(default)]
+    pub path_template: Option<String>,
+    /// Overrides the `env` of `.manymany.toml` and the user-level settings
+    #[serde(default)]
+    pub env: BTreeMap<String, String>,
+    /// Replaces the terminals from `.manymany.toml` when set
+    #[serde(default)]
+    pub terminals: Option<Vec<TerminalPreset>>,
+    /// Branches never checked out in a second worktree. Defaults to the default branch.
+    #[serde(default)]
+    pub protected_branches: Option<Vec<String>>,
+    /// The `setup` commands of `.manymany.toml` the user approved. They only run while the
+    /// file lists exactly these, so a changed list needs approving again.
+    #[serde(default)]
+    pub trusted_setup: Option<Vec<String>>,
+    /// The `[[terminals]]` of `.manymany.toml` the user approved to run on their own
+    #[serde(default)]
+    pub trusted_terminals: Option<Vec<TerminalPreset>>,
+    /// The `[env]` of `.manymany.toml` the user approved for terminals and editors
+    #[serde(default)]
+    pub trusted_env: Option<BTreeMap<String, String>>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct Worktree {
     pub id: String,
     pub branch: String,
@@ -40,6 +82,33 @@ pub struct WorkspaceRepo {
     pub is_git_repo: bool,
 }
 
+/// A workspace file's repositories, plus the sections the frontend may want to show
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ParsedWorkspace {
+    pub repos: Vec<WorkspaceRepo>,
+    /// Folder entries that couldn't be used, e.g. because the path doesn't exist
+    pub skipped_folders: Vec<SkippedFolder>,
+    pub settings: serde_json::Map<String, serde_json::Value>,
+    pub extensions: Option<WorkspaceExtensions>,
+    pub launch: Option<WorkspaceLaunch>,
+}
+
+/// Repositories listed by a multi-repo manifest
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ParsedManifest {
+    /// "repo", "gitmodules", "mani" or "meta"
+    pub format: String,
+    pub repos: Vec<WorkspaceRepo>,
+    pub skipped_folders: Vec<SkippedFolder>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SkippedFolder {
+    pub name: Option<String>,
+    pub path: String,
+    pub reason: String,
+}
+
 #[tauri::command]
 pub async fn add_project(request: AddProjectRequest) -> Result<Project, String> {
     let project_path = PathBuf::from(&request.path);
@@ -49,13 +118,18 @@ pub async fn add_project(request: AddProjectRequest) -> Result<Project, String>
     }
     
     // Validate project type
-    if request.project_type == "repository" {
-        if !project_path.join(".git").exists() {
-            return Err("Selected folder is not a Git repository".to_string());
-        }
-    } else if request.project_type == "workspace" {
-        if !request.path.ends_with(".code-workspace") && !request.path.ends_with(".json") {
-            return Err("Selected file is not a valid workspace file".to_string());
+    let repository = if request.project_type == "repository" {
+        // Ask git rather than looking for `.git`, so bare repos and gitfiles are accepted
+        Some(detect_repository(&project_path)
+            .ok_or_else(|| "Selected folder is not a Git repository".to_string())?)
+    } else {
+        None
+    };
+    
+    if request.project_type == "workspace" {
+        let is_workspace_file = request.path.ends_with(".code-workspace") || request.path.ends_with(".json");
+        if !is_workspace_file && ManifestFormat::detect(&project_path).is_none() {
+            return Err("Selected file is not a valid workspace or manifest file".to_string());
         }
     }
     
@@ -67,38 +141,38 @@ pub async fn add_project(request: AddProjectRequest) -> Result<Project, String>
         default_branch: request.default_branch,
         created_at: Utc::now().to_rfc3339(),
         worktrees: vec![],
+        git_common_dir: repository.as_ref().map(|r| r.common_dir.clone()),
+        is_bare: repository.map(|r| r.is_bare).unwrap_or(false),
+        settings: ProjectSettings::default(),
     };
     
+    ProjectRegistry::update(|registry| registry.insert(project.clone()))?;
+    
     Ok(project)
 }
 
 #[tauri::command]
 pub async fn list_projects() -> Result<Vec<Project>, String> {
-    // TODO: Load from persistent storage
-    Ok(vec![])
+    let registry = ProjectRegistry::load()?;
+    Ok(registry.projects().to_vec())
 }
 
 #[tauri::command]
-pub async fn remove_project(_id: String) -> Result<(), String> {
-    // TODO: Remove from persistent storage
-    Ok(())
+pub async fn remove_project(id: String) -> Result<(), String> {
+    ProjectRegistry::update(|registry| {
+        registry
+            .remove(&id)
+            .map(|_| ())
+            .ok_or_else(|| "Project not found".to_string())
+    })
 }
 
 #[tauri::command]
 pub fn get_default_branch(path: String) -> Result<String, String> {
     use std::process::Command;
     
-    let output = Command::new("git")
-        .args(&["-C", &path, "symbolic-ref", "refs/remotes/origin/HEAD"])
-        .output()
-        .map_err(|e| format!("Failed to execute git command: {}", e))?;
-    
-    if output.status.success() {
-        let branch = String::from_utf8_lossy(&output.stdout);
-        let branch = branch.trim().replace("refs/remotes/origin/", "");
-        if !branch.is_empty() {
-            return Ok(branch);
-        }
+    if let Some(branch) = remote_default_branch(&path) {
+        return Ok(branch);
     }
     
     // Fallback to checking current branch
@@ -118,85 +192,149 @@ pub fn get_default_branch(path: String) -> Result<String, String> {
     Ok("main".to_string())
 }
 
+/// The branch `origin/HEAD` points to, if the remote's default branch is known
+pub fn remote_default_branch(path: &str) -> Option<String> {
+    let branch = git(path, &["symbolic-ref", "refs/remotes/origin/HEAD"]).ok()?;
+    let branch = branch.trim().replace("refs/remotes/origin/", "");
+    Some(branch).filter(|b| !b.is_empty())
+}
+
+/// Effective configuration for a project or worktree: `.manymany.toml` merged with user settings
 #[tauri::command]
-pub async fn parse_workspace_file(workspace_path: String) -> Result<Vec<WorkspaceRepo>, String> {
+pub async fn get_project_config(path: String) -> Result<ProjectConfig, String> {
+    ProjectConfig::load(&path)
+}
+
+#[tauri::command]
+pub async fn parse_workspace_file(workspace_path: String) -> Result<ParsedWorkspace, String> {
     let workspace_path = PathBuf::from(&workspace_path);
     
     if !workspace_path.exists() {
         return Err("Workspace file does not exist".to_string());
     }
     
-    // Read and parse the workspace file
-    let content = fs::read_to_string(&workspace_path)
-        .map_err(|e| format!("Failed to read workspace file: {}", e))?;
-    
-    let workspace_data: serde_json::Value = serde_json::from_str(&content)
-        .map_err(|e| format!("Failed to parse workspace file: {}", e))?;
+    // VS Code allows comments and trailing commas in workspace files
+    let workspace = WorkspaceFile::load(&workspace_path)?;
     
     let mut repos = Vec::new();
+    let mut skipped_folders = Vec::new();
     
-    // Extract folders from workspace
-    if let Some(folders) = workspace_data.get("folders").and_then(|f| f.as_array()) {
-        for folder in folders {
-            if let Some(path_str) = folder.get("path").and_then(|p| p.as_str()) {
-                let folder_path = if path_str.starts_with("./") || path_str.starts_with("../") {
-                    // Relative path - resolve relative to workspace file
-                    workspace_path.parent()
-                        .unwrap_or(&workspace_path)
-                        .join(path_str)
-                        .canonicalize()
-                        .unwrap_or_else(|_| workspace_path.parent().unwrap().join(path_str))
-                } else if path_str.starts_with("/") {
-                    // Absolute path
-                    PathBuf::from(path_str)
-                } else {
-                    // Relative path without ./
-                    workspace_path.parent()
-                        .unwrap_or(&workspace_path)
-                        .join(path_str)
-                        .canonicalize()
-                        .unwrap_or_else(|_| workspace_path.parent().unwrap().join(path_str))
-                };
-                
-                if folder_path.exists() {
-                    let folder_name = folder.get("name")
-                        .and_then(|n| n.as_str())
-                        .unwrap_or_else(|| {
-                            folder_path.file_name()
-                                .and_then(|n| n.to_str())
-                                .unwrap_or("Unknown")
-                        });
-                    
-                    // Check if it's a Git repository
-                    let is_git_repo = folder_path.join(".git").exists();
-                    
-                    // Get default branch if it's a Git repo
-                    let default_branch = if is_git_repo {
-                        get_default_branch(folder_path.to_string_lossy().to_string())
-                            .unwrap_or_else(|_| "main".to_string())
-                    } else {
-                        "main".to_string()
-                    };
-                    
-                    repos.push(WorkspaceRepo {
-                        name: folder_name.to_string(),
-                        path: folder_path.to_string_lossy().to_string(),
-                        default_branch,
-                        is_git_repo,
-                    });
-                }
+    for folder in &workspace.folders {
+        let folder_path = match folder.resolve(&workspace_path) {
+            Some(path) => path,
+            None => {
+                skipped_folders.push(SkippedFolder {
+                    name: folder.name.clone(),
+                    path: folder.uri.clone().unwrap_or_default(),
+                    reason: "Folder has no local path".to_string(),
+                });
+                continue;
             }
+        };
+        
+        if !folder_path.exists() {
+            skipped_folders.push(SkippedFolder {
+                name: folder.name.clone(),
+                path: folder_path.to_string_lossy().to_string(),
+                reason: "Folder does not exist".to_string(),
+            });
+            continue;
+        }
+        
+        let folder_name = folder.name.clone().unwrap_or_else(|| {
+            folder_path.file_name()
+                .and_then(|n| n.to_str())
+                .unwrap_or("Unknown")
+                .to_string()
+        });
+        
+        repos.push(workspace_repo(folder_name, &folder_path, None));
+    }
+    
+    Ok(ParsedWorkspace {
+        repos,
+        skipped_folders,
+        settings: workspace.settings,
+        extensions: workspace.extensions,
+        launch: workspace.launch,
+    })
+}
+
+/// Read the repositories of a `repo` XML manifest, `.gitmodules`, `mani.yaml` or `.meta` file
+#[tauri::command]
+pub async fn parse_manifest_file(manifest_path: String) -> Result<ParsedManifest, String> {
+    let manifest_path = PathBuf::from(&manifest_path);
+    
+    if !manifest_path.exists() {
+        return Err("Manifest file does not exist".to_string());
+    }
+    
+    let (format, entries) = load_manifest(&manifest_path)?;
+    
+    let mut repos = Vec::new();
+    let mut skipped_folders = Vec::new();
+    
+    for entry in entries {
+        // Repos that haven't been cloned or synced yet
+        if !entry.path.exists() {
+            skipped_folders.push(SkippedFolder {
+                name: Some(entry.name),
+                path: entry.path.to_string_lossy().to_string(),
+                reason: "Folder does not exist".to_string(),
+            });
+            continue;
         }
+        
+        repos.push(workspace_repo(entry.name, &entry.path, entry.branch));
     }
     
-    Ok(repos)
+    Ok(ParsedManifest {
+        format: format.as_str().to_string(),
+        repos,
+        skipped_folders,
+    })
+}
+
+/// Repos of a workspace project, which is either a `.code-workspace` file or a multi-repo manifest
+pub async fn workspace_project_repos(path: String) -> Result<(Vec<WorkspaceRepo>, Vec<SkippedFolder>), String> {
+    if ManifestFormat::detect(Path::new(&path)).is_some() {
+        let parsed = parse_manifest_file(path).await?;
+        Ok((parsed.repos, parsed.skipped_folders))
+    } else {
+        let parsed = parse_workspace_file(path).await?;
+        Ok((parsed.repos, parsed.skipped_folders))
+    }
+}
+
+/// Describe a workspace folder, preferring `branch` over the repo's detected default branch
+fn workspace_repo(name: String, path: &Path, branch: Option<String>) -> WorkspaceRepo {
+    // Check if it's a Git repository
+    let is_git_repo = detect_repository(path).is_some();
+    
+    // Get default branch if it's a Git repo
+    let default_branch = match branch {
+        Some(branch) => branch,
+        None if is_git_repo => get_default_branch(path.to_string_lossy().to_string())
+            .unwrap_or_else(|_| "main".to_string()),
+        None => "main".to_string(),
+    };
+    
+    WorkspaceRepo {
+        name,
+        path: path.to_string_lossy().to_string(),
+        default_branch,
+        is_git_repo,
+    }
 }
 
 #[tauri::command]
 pub async fn open_in_app(path: String, app: String) -> Result<(), String> {
+    // Editors inherit the project's environment overrides, like terminals do
+    let env = ProjectConfig::load_env(&path);
+    
     let command_result = match app.as_str() {
-        "cursor" => Command::new("cursor").arg(&path).spawn(),
-        "vscode" => Command::new("code").arg(&path).spawn(),
+        "cursor" => Command::new("cursor").arg(&path).envs(&env).spawn(),
+        "vscode" => Command::new("code").arg(&path).envs(&env).spawn(),
         "finder" => {
             #[cfg(target_os = "macos")]
             {
@@ -224,4 +362,59 @@ pub async fn open_in_app(path: String, app: String) -> Result<(), String> {
             Err(format!("Failed to open in {}: {}", app, e))
         }
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support;
+    use std::fs;
+
+    #[tokio::test]
+    async fn test_add_project_rejects_subdirectories() {
+        let env = test_support::data_dir();
+        let repo = test_support::init_repo(&env.root);
+        fs::create_dir_all(repo.join("src")).unwrap();
+
+        let request = |path: &Path| AddProjectRequest {
+            name: "repo".to_string(),
+            path: path.to_string_lossy().to_string(),
+            project_type: "repository".to_string(),
+            default_branch: None,
+            workspace_repos: None,
+        };
+        assert!(add_project(request(&repo.join("src"))).await.is_err());
+        assert!(add_project(request(&repo)).await.is_ok());
+
+        let _ = fs::remove_dir_all(&env.root);
+    }
+
+    #[tokio::test]
+    async fn test_add_project_accepts_manifests() {
+        let env = test_support::data_dir();
+        let repo = test_support::init_repo(&env.root);
+        let request = |path: &Path| AddProjectRequest {
+            name: "stack".to_string(),
+            path: path.to_string_lossy().to_string(),
+            project_type: "workspace".to_string(),
+            default_branch: None,
+            workspace_repos: None,
+        };
+
+        let manifest = env.root.join("mani.yaml");
+        fs::write(&manifest, "projects:\n  repo:\n    branch: develop\n").unwrap();
+        let project = add_project(request(&manifest)).await.unwrap();
+
+        let (repos, skipped) = workspace_project_repos(project.path).await.unwrap();
+        assert_eq!(repos.len(), 1);
+        assert_eq!(repos[0].path, repo.to_string_lossy());
+        assert_eq!(repos[0].default_branch, "develop");
+        assert!(skipped.is_empty());
+
+        let other = env.root.join("notes.txt");
+        fs::write(&other, "").unwrap();
+        assert!(add_project(request(&other)).await.is_err());
+
+        let _ = fs::remove_dir_all(&env.root);
+    }
+}
@@ -0,0 +1,301 @@
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::process::Command;
+use chrono::Utc;
+use uuid::Uuid;
+
+use crate::git_commands::git;
+
+/// Namespace for snapshots taken before a worktree is removed
+pub const BACKUP_REF_PREFIX: &str = "refs/manymany/backups";
+
+/// What would be lost by removing a worktree
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RemovalCheck {
+    pub worktree_path: String,
+    pub branch: Option<String>,
+    /// Modified, staged or deleted tracked files
+    pub uncommitted: Vec<String>,
+    pub untracked: Vec<String>,
+    /// Stashes made on this worktree's branch
+    pub stashes: Vec<String>,
+    /// Commits on this branch that are on no remote and no other local branch
+    pub unpushed_commits: Vec<String>,
+    /// Nothing above was found, so removal loses nothing
+    pub safe: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct RemoveWorktreeOptions {
+    /// Remove even though the preflight found work that would be lost
+    #[serde(default)]
+    pub confirmed: bool,
+    /// Snapshot the worktree to a ref under `refs/manymany/backups` first
+    #[serde(default)]
+    pub backup: bool,
+    /// Delete the worktree's branch after removing it
+    #[serde(default)]
+    pub delete_branch: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize, Default)]
+pub struct RemoveWorktreeResult {
+    pub backup_ref: Option<String>,
+    pub deleted_branch: Option<String>,
+}
+
+#[tauri::command]
+pub async fn check_worktree_removal(worktree_path: String) -> Result<RemovalCheck, String> {
+    removal_check(&worktree_path)
+}
+
+pub(crate) fn removal_check(worktree_path: &str) -> Result<RemovalCheck, String> {
+    if !Path::new(worktree_path).exists() {
+        // Nothing on disk to lose; git only has stale metadata
+        return Ok(RemovalCheck {
+            worktree_path: worktree_path.to_string(),
+            branch: None,
+            uncommitted: Vec::new(),
+            untracked: Vec::new(),
+            stashes: Vec::new(),
+            unpushed_commits: Vec::new(),
+            safe: true,
+        });
+    }
+
+    let branch = git(worktree_path, &["branch", "--show-current"])
+        .ok()
+        .filter(|b| !b.is_empty());
+
+    let mut uncommitted = Vec::new();
+    let mut untracked = Vec::new();
+    // NUL separated, so paths come unquoted and renames don't need splitting on " -> "
+    let status = git(worktree_path, &["status", "--porcelain=v1", "-z", "--untracked-files=all"])?;
+    let mut entries = status.split('\0');
+    while let Some(entry) = entries.next() {
+        if entry.len() < 4 {
+            continue;
+        }
+        let (code, path) = entry.split_at(3);
+        if code.starts_with("??") {
+            untracked.push(path.to_string());
+        } else {
+            // Renames and copies are followed by the path they came from
+            if code.contains('R') || code.contains('C') {
+                entries.next();
+            }
+            uncommitted.push(path.to_string());
+        }
+    }
+
+    let stashes = match &branch {
+        Some(branch) => git(worktree_path, &["stash", "list", "--format=%gd: %gs"])
+            .unwrap_or_default()
+            .lines()
+            .filter(|s| s.contains(&format!("On {}: ", branch)) || s.contains(&format!("WIP on {}: ", branch)))
+            .map(String::from)
+            .collect(),
+        None => Vec::new(),
+    };
+
+    // Commits are safe once a remote or another branch has them
+    let mut args = vec!["log", "--format=%h %s", "HEAD", "--not", "--remotes"];
+    // --exclude patterns for --branches are relative to refs/heads
+    let exclude = branch.as_ref().map(|b| format!("--exclude={}", b));
+    if let Some(exclude) = &exclude {
+        args.push(exclude);
+    }
+    args.push("--branches");
+    let unpushed_commits: Vec<String> = git(worktree_path, &args)
+        .unwrap_or_default()
+        .lines()
+        .map(String::from)
+        .collect();
+
+    let safe = uncommitted.is_empty() && untracked.is_empty() && stashes.is_empty() && unpushed_commits.is_empty();
+
+    Ok(RemovalCheck {
+        worktree_path: worktree_path.to_string(),
+        branch,
+        uncommitted,
+        untracked,
+        stashes,
+        unpushed_commits,
+        safe,
+    })
+}
+
+/// One line summary of what the preflight found, for error messages
+pub(crate) fn describe_risks(check: &RemovalCheck) -> String {
+    let counts = [
+        (check.uncommitted.len(), "uncommitted file"),
+        (check.untracked.len(), "untracked file"),
+        (check.stashes.len(), "stash"),
+        (check.unpushed_commits.len(), "unpushed commit"),
+    ];
+    counts
+        .iter()
+        .filter(|(count, _)| *count > 0)
+        .map(|(count, what)| format!("{} {}{}", count, what, if *count == 1 { "" } else { "s" }))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Commit the worktree's current state, including untracked files, to a backup ref.
+///
+/// Uses a temporary index so the worktree's own index and files are left untouched.
+pub(crate) fn backup_worktree(worktree_path: &str, branch: Option<&str>) -> Result<String, String> {
+    let index_path = std::env::temp_dir().join(format!("manymany-backup-{}.index", Uuid::new_v4()));
+    let result = snapshot_commit(worktree_path, &index_path);
+    let _ = std::fs::remove_file(&index_path);
+    let commit = result?;
+
+    let name = branch.map(String::from).unwrap_or_else(|| {
+        Path::new(worktree_path)
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_default()
+    });
+    // Two backups in the same second still get their own ref
+    let unique = Uuid::new_v4().to_string();
+    let mut backup_ref = format!(
+        "{}/{}/{}-{}",
+        BACKUP_REF_PREFIX,
+        sanitize_ref_name(&name),
+        Utc::now().format("%Y%m%d-%H%M%S"),
+        &unique[..8]
+    );
+    if git(worktree_path, &["check-ref-format", &backup_ref]).is_err() {
+        backup_ref = format!("{}/worktree/{}", BACKUP_REF_PREFIX, unique);
+    }
+    git(worktree_path, &["update-ref", &backup_ref, &commit])
+        .map_err(|e| format!("Failed to save backup: {}", e))?;
+
+    Ok(backup_ref)
+}
+
+/// `name` with what git doesn't allow in ref names replaced, e.g. for a folder named `my work~2`
+fn sanitize_ref_name(name: &str) -> String {
+    let replaced: String = name
+        .chars()
+        .map(|c| match c {
+            ' ' | '~' | '^' | ':' | '?' | '*' | '[' | '\\' => '-',
+            c if c.is_control() => '-',
+            c => c,
+        })
+        .collect();
+    let replaced = replaced.replace("..", "-").replace("@{", "-");
+
+    let name = replaced
+        .split('/')
+        .map(|part| part.trim_start_matches('.').trim_end_matches(".lock").trim_end_matches('.'))
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>()
+        .join("/");
+    if name.is_empty() || name == "@" {
+        "worktree".to_string()
+    } else {
+        name
+    }
+}
+
+fn snapshot_commit(worktree_path: &str, index_path: &Path) -> Result<String, String> {
+    let with_index = |args: &[&str]| -> Result<String, String> {
+        let output = Command::new("git")
+            .args(&["-C", worktree_path])
+            .args(args)
+            .env("GIT_INDEX_FILE", index_path)
+            .output()
+            .map_err(|e| format!("Failed to back up worktree: {}", e))?;
+        if !output.status.success() {
+            return Err(format!(
+                "Failed to back up worktree: {}",
+                String::from_utf8_lossy(&output.stderr)
+            ));
+        }
+        Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+    };
+
+    let head = git(worktree_path, &["rev-parse", "--verify", "--quiet", "HEAD"]).ok();
+    if head.is_some() {
+        with_index(&["read-tree", "HEAD"])?;
+    }
+    with_index(&["add", "--all"])?;
+    let tree = with_index(&["write-tree"])?;
+
+    let message = format!("ManyMany backup of {}", worktree_path);
+    let mut args = vec!["commit-tree", tree.as_str(), "-m", message.as_str()];
+    if let Some(head) = &head {
+        args.push("-p");
+        args.push(head);
+    }
+    with_index(&args)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{self, commit_file, git as run};
+    use std::fs;
+
+    #[test]
+    fn test_removal_check_finds_work_that_would_be_lost() {
+        let root = test_support::temp_dir("removal");
+        let repo = test_support::init_repo(&root);
+        let worktree = root.join("feature");
+        run(&repo, &["worktree", "add", "-q", "-b", "feature", &worktree.to_string_lossy()]);
+        let path = worktree.to_string_lossy().to_string();
+        assert!(removal_check(&path).unwrap().safe);
+
+        fs::write(worktree.join("a.txt"), "stashed").unwrap();
+        run(&worktree, &["stash", "push", "-q", "-m", "wip"]);
+        commit_file(&worktree, "b.txt", "b", "local work");
+        fs::write(worktree.join("a.txt"), "changed").unwrap();
+        fs::write(worktree.join("new file é.txt"), "new").unwrap();
+        run(&worktree, &["mv", "b.txt", "renamed b.txt"]);
+
+        let check = removal_check(&path).unwrap();
+        assert_eq!(check.branch.as_deref(), Some("feature"));
+        assert_eq!(check.uncommitted, vec!["a.txt", "renamed b.txt"]);
+        assert_eq!(check.untracked, vec!["new file é.txt"]);
+        assert_eq!(check.stashes.len(), 1);
+        assert!(check.stashes[0].contains("wip"));
+        assert_eq!(check.unpushed_commits.len(), 1);
+        assert!(check.unpushed_commits[0].ends_with("local work"));
+        assert!(!check.safe);
+        assert_eq!(
+            describe_risks(&check),
+            "2 uncommitted files, 1 untracked file, 1 stash, 1 unpushed commit"
+        );
+
+        let _ = fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn test_backup_worktree_snapshots_files_under_a_valid_ref() {
+        let root = test_support::temp_dir("backup");
+        let repo = test_support::init_repo(&root);
+        let worktree = root.join("my work~2^[x]");
+        run(&repo, &["worktree", "add", "-q", "--detach", &worktree.to_string_lossy()]);
+        let path = worktree.to_string_lossy().to_string();
+        fs::write(worktree.join("a.txt"), "changed").unwrap();
+        fs::write(worktree.join("new.txt"), "new").unwrap();
+
+        let first = backup_worktree(&path, None).unwrap();
+        let second = backup_worktree(&path, None).unwrap();
+        assert_ne!(first, second);
+        assert!(first.starts_with(&format!("{}/my-work-2--x]/", BACKUP_REF_PREFIX)));
+
+        assert_eq!(run(&repo, &["show", &format!("{}:a.txt", first)]), "changed");
+        assert_eq!(run(&repo, &["show", &format!("{}:new.txt", first)]), "new");
+        assert_eq!(run(&repo, &["rev-parse", &format!("{}^", first)]), run(&repo, &["rev-parse", "main"]));
+        // The worktree itself is left as it was
+        assert_eq!(run(&worktree, &["status", "--porcelain"]), " M a.txt\n?? new.txt");
+
+        assert_eq!(sanitize_ref_name("feature/login"), "feature/login");
+        assert_eq!(sanitize_ref_name(".hidden..name.lock"), "hidden-name");
+        assert_eq!(sanitize_ref_name("@"), "worktree");
+
+        let _ = fs::remove_dir_all(&root);
+    }
+}
@@ -0,0 +1,348 @@
+use serde::{Deserialize, Serialize};
+use tauri::AppHandle;
+
+use crate::commands::bootstrap::spawn_bootstrap;
+use crate::commands::removal::backup_worktree;
+use crate::commands::worktree::{
+    add_worktree, resolve_project_id, CreateWorktreeOptions, Worktree,
+};
+use crate::git_commands::{git, local_branch_exists};
+use crate::store::{SourceRef, WorktreeStore};
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RefreshResult {
+    pub source: SourceRef,
+    pub status: String, // "up_to_date", "updated", "diverged" or "reset"
+    pub previous_head: String,
+    /// The fetched commit; HEAD unless the refresh was refused as diverged
+    pub fetched_head: String,
+    /// Where the worktree's state was saved before a forced reset
+    pub backup_ref: Option<String>,
+}
+
+/// Fetch `source` into a new local `branch` and open a worktree on it,
+/// e.g. `refs/pull/123/head` from `origin` to review a pull request
+#[tauri::command]
+pub async fn create_worktree_from_ref(
+    app: AppHandle,
+    project_path: String,
+    project_id: String,
+    source: SourceRef,
+    branch: String,
+    worktree_name: String,
+    options: Option<CreateWorktreeOptions>,
+) -> Result<Worktree, String> {
+    let source = SourceRef {
+        remote: source.remote.trim().to_string(),
+        ref_name: source.ref_name.trim().to_string(),
+    };
+    // Fetching and seeding caches block, so keep them off the async runtime
+    let path = project_path.clone();
+    let worktree = tokio::task::spawn_blocking(move || {
+        add_worktree_from_ref(
+            &path,
+            project_id,
+            source,
+            branch.trim(),
+            worktree_name,
+            &options.unwrap_or_default(),
+        )
+    })
+    .await
+    .map_err(|e| format!("Failed to create worktree: {}", e))??;
+    // The fetched code hasn't been reviewed yet, so its setup waits for the user
+    spawn_bootstrap(&app, &project_path, &worktree.path, false);
+    Ok(worktree)
+}
+
+pub(crate) fn add_worktree_from_ref(
+    project_path: &str,
+    project_id: String,
+    source: SourceRef,
+    branch: &str,
+    worktree_name: String,
+    options: &CreateWorktreeOptions,
+) -> Result<Worktree, String> {
+    if source.remote.is_empty() || source.ref_name.is_empty() || branch.is_empty() {
+        return Err("A remote, a ref to fetch and a branch name are required".to_string());
+    }
+    validate_source(project_path, &source)?;
+    // Fetching would otherwise overwrite the branch, or fail if it has moved on
+    if local_branch_exists(project_path, branch) {
+        return Err(format!(
+            "Branch '{}' already exists.\n\nPlease choose a different name or check out the existing branch.",
+            branch
+        ));
+    }
+
+    let refspec = format!("{}:refs/heads/{}", source.ref_name, branch);
+    git(
+        project_path,
+        &["fetch", "--no-tags", &source.remote, &refspec],
+    )
+    .map_err(|e| {
+        format!(
+            "Failed to fetch {} from {}: {}",
+            source.ref_name, source.remote, e
+        )
+    })?;
+
+    let options = CreateWorktreeOptions {
+        create_branch: false,
+        ..options.clone()
+    };
+    let mut worktree = match add_worktree(
+        project_path,
+        branch,
+        project_id.clone(),
+        worktree_name,
+        &options,
+    ) {
+        Ok(worktree) => worktree,
+        Err(e) => {
+            // The branch was only fetched for this worktree
+            let _ = git(project_path, &["branch", "-D", branch]);
+            return Err(e);
+        }
+    };
+
+    if let Some(id) = resolve_project_id(project_path, Some(project_id)) {
+        if let Err(e) = WorktreeStore::update(&id, |store| {
+            if let Some(record) = store.records_mut().iter_mut().find(|r| r.id == worktree.id) {
+                record.source_ref = Some(source.clone());
+            }
+            Ok(())
+        }) {
+            eprintln!(
+                "Failed to record the source ref of {}: {}",
+                worktree.path, e
+            );
+        }
+    }
+    worktree.source_ref = Some(source);
+    Ok(worktree)
+}
+
+/// Only configured remotes can be fetched from, and nothing that git would take for an option
+fn validate_source(project_path: &str, source: &SourceRef) -> Result<(), String> {
+    let remotes = git(project_path, &["remote"])?;
+    if !remotes.lines().any(|remote| remote.trim() == source.remote) {
+        return Err(format!(
+            "'{}' is not a remote of this repository",
+            source.remote
+        ));
+    }
+    if source.ref_name.starts_with('-') {
+        return Err(format!("Invalid ref to fetch: {}", source.ref_name));
+    }
+    Ok(())
+}
+
+/// Fetch a worktree's source ref again and fast-forward to it.
+///
+/// When the ref was force-pushed or the worktree has its own commits, nothing changes and
+/// the result is "diverged", unless `force` resets the worktree to the fetched commit.
+/// The worktree's state is saved to a backup ref before it is reset.
+#[tauri::command]
+pub async fn refresh_worktree_ref(
+    project_path: String,
+    worktree_path: String,
+    project_id: Option<String>,
+    force: Option<bool>,
+) -> Result<RefreshResult, String> {
+    // Fetching blocks, so keep it off the async runtime
+    tokio::task::spawn_blocking(move || {
+        refresh_ref(
+            &project_path,
+            &worktree_path,
+            project_id,
+            force.unwrap_or(false),
+        )
+    })
+    .await
+    .map_err(|e| format!("Failed to refresh worktree: {}", e))?
+}
+
+fn refresh_ref(
+    project_path: &str,
+    worktree_path: &str,
+    project_id: Option<String>,
+    force: bool,
+) -> Result<RefreshResult, String> {
+    let id = resolve_project_id(project_path, project_id)
+        .ok_or_else(|| "Project not found".to_string())?;
+    let record = WorktreeStore::load(&id)?
+        .find_by_path(worktree_path)
+        .cloned()
+        .ok_or_else(|| "Worktree wasn't created from a fetched ref".to_string())?;
+    let source = record
+        .source_ref
+        .ok_or_else(|| "Worktree wasn't created from a fetched ref".to_string())?;
+    validate_source(project_path, &source)?;
+
+    let status = git(
+        worktree_path,
+        &["status", "--porcelain", "--untracked-files=no"],
+    )?;
+    if !status.is_empty() {
+        return Err(
+            "Worktree has uncommitted changes.\n\nCommit or stash them before refreshing."
+                .to_string(),
+        );
+    }
+
+    git(
+        worktree_path,
+        &["fetch", "--no-tags", &source.remote, &source.ref_name],
+    )
+    .map_err(|e| {
+        format!(
+            "Failed to fetch {} from {}: {}",
+            source.ref_name, source.remote, e
+        )
+    })?;
+    let fetched_head = git(worktree_path, &["rev-parse", "FETCH_HEAD^{commit}"])?;
+    let previous_head = git(worktree_path, &["rev-parse", "HEAD"])?;
+
+    let mut backup_ref = None;
+    let status = if fetched_head == previous_head {
+        "up_to_date"
+    } else if git(
+        worktree_path,
+        &["merge-base", "--is-ancestor", "HEAD", &fetched_head],
+    )
+    .is_ok()
+    {
+        git(worktree_path, &["merge", "--ff-only", &fetched_head])
+            .map_err(|e| format!("Failed to fast-forward: {}", e))?;
+        "updated"
+    } else if force {
+        // The worktree's own commits are only reachable from the branch being reset
+        backup_ref = Some(backup_worktree(worktree_path, Some(&record.branch))?);
+        git(worktree_path, &["reset", "--hard", &fetched_head])
+            .map_err(|e| format!("Failed to reset to the fetched commit: {}", e))?;
+        "reset"
+    } else {
+        "diverged"
+    };
+
+    Ok(RefreshResult {
+        source,
+        status: status.to_string(),
+        previous_head,
+        fetched_head,
+        backup_ref,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{self, commit_file, git as run};
+    use std::fs;
+
+    #[tokio::test]
+    async fn test_worktree_from_ref_and_refresh() {
+        let env = test_support::data_dir();
+        let repo = test_support::init_repo(&env.root);
+        let project_id = test_support::add_project(&repo).await;
+        let project_path = repo.to_string_lossy().to_string();
+
+        // A colleague pushes a pull request ref
+        let other = env.root.join("other");
+        run(&env.root, &["clone", "-q", "origin.git", "other"]);
+        run(&other, &["config", "user.email", "other@example.com"]);
+        run(&other, &["config", "user.name", "Other"]);
+        let push = |message: &str| {
+            commit_file(&other, "pr.txt", message, message);
+            run(
+                &other,
+                &["push", "-q", "-f", "origin", "HEAD:refs/pull/7/head"],
+            );
+        };
+        push("one");
+
+        let source = SourceRef {
+            remote: "origin".to_string(),
+            ref_name: "refs/pull/7/head".to_string(),
+        };
+        let options = CreateWorktreeOptions::default();
+        let worktree = add_worktree_from_ref(
+            &project_path,
+            project_id.clone(),
+            source.clone(),
+            "pr-7",
+            "pr-7".to_string(),
+            &options,
+        )
+        .unwrap();
+        assert_eq!(worktree.branch, "pr-7");
+        assert_eq!(worktree.source_ref.as_ref(), Some(&source));
+        let pr_file = std::path::Path::new(&worktree.path).join("pr.txt");
+        assert_eq!(fs::read_to_string(&pr_file).unwrap(), "one");
+
+        // The branch exists now, so a second fetch into it is refused
+        let again = add_worktree_from_ref(
+            &project_path,
+            project_id.clone(),
+            source.clone(),
+            "pr-7",
+            "again".to_string(),
+            &options,
+        );
+        assert!(again.unwrap_err().contains("already exists"));
+
+        let refresh = |force: Option<bool>| {
+            refresh_worktree_ref(
+                project_path.clone(),
+                worktree.path.clone(),
+                Some(project_id.clone()),
+                force,
+            )
+        };
+        assert_eq!(refresh(None).await.unwrap().status, "up_to_date");
+
+        push("two");
+        assert_eq!(refresh(None).await.unwrap().status, "updated");
+        assert_eq!(fs::read_to_string(&pr_file).unwrap(), "two");
+
+        // A force-push rewrites the ref; only `force` follows it
+        run(&other, &["reset", "-q", "--hard", "HEAD~1"]);
+        push("rewritten");
+        assert_eq!(refresh(None).await.unwrap().status, "diverged");
+        assert_eq!(fs::read_to_string(&pr_file).unwrap(), "two");
+        let reset = refresh(Some(true)).await.unwrap();
+        assert_eq!(reset.status, "reset");
+        assert_eq!(fs::read_to_string(&pr_file).unwrap(), "rewritten");
+        // What the reset dropped is kept in the backup
+        let backup = reset.backup_ref.unwrap();
+        assert_eq!(run(&repo, &["show", &format!("{}:pr.txt", backup)]), "two");
+
+        // Options smuggled in as the remote or the ref are never passed to git
+        let marker = env.root.join("pwned");
+        let hostile = |remote: &str, ref_name: &str| SourceRef {
+            remote: remote.to_string(),
+            ref_name: ref_name.to_string(),
+        };
+        for source in [
+            hostile(&format!("--upload-pack=touch {}", marker.display()), "main"),
+            hostile(
+                "origin",
+                &format!("--upload-pack=touch {}", marker.display()),
+            ),
+        ] {
+            let result = add_worktree_from_ref(
+                &project_path,
+                project_id.clone(),
+                source,
+                "hostile",
+                "hostile".to_string(),
+                &options,
+            );
+            assert!(result.is_err());
+        }
+        assert!(!marker.exists());
+
+        let _ = fs::remove_dir_all(&env.root);
+    }
+}
@@ -0,0 +1,189 @@
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io;
+use std::path::Path;
+
+/// Directories seeded when the project config doesn't list its own.
+///
+/// Virtualenvs are left out: their scripts hold absolute paths into the checkout they were
+/// made in, so a seeded `.venv` would run the main checkout's code and must be rebuilt anyway.
+pub const DEFAULT_SEED_DIRS: &[&str] = &["node_modules", "target"];
+
+/// How one directory was seeded into a new worktree
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SeededDir {
+    pub dir: String,
+    /// "reflink", "copy" or "mixed"; empty when nothing was seeded
+    pub method: String,
+    pub files: u64,
+    pub bytes: u64,
+    /// Why the directory was left alone, e.g. missing in the source
+    pub skipped: Option<String>,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct SeedReport {
+    pub source: String,
+    pub dirs: Vec<SeededDir>,
+    pub total_bytes: u64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Method {
+    Reflink,
+    Copy,
+}
+
+impl Method {
+    fn as_str(&self) -> &'static str {
+        match self {
+            Method::Reflink => "reflink",
+            Method::Copy => "copy",
+        }
+    }
+}
+
+#[derive(Default)]
+struct Progress {
+    files: u64,
+    bytes: u64,
+    methods: Vec<Method>,
+}
+
+/// Seed `dirs` from the `source` checkout into the new worktree at `target`.
+///
+/// Files are cloned where the filesystem supports it and copied otherwise. Hardlinks are
+/// never used, since editing a linked file in the worktree would change the source's too.
+pub(crate) fn seed_caches(source: &str, target: &str, dirs: &[String]) -> SeedReport {
+    let mut report = SeedReport {
+        source: source.to_string(),
+        ..Default::default()
+    };
+
+    for dir in dirs {
+        let from = Path::new(source).join(dir);
+        let to = Path::new(target).join(dir);
+        let mut seeded = SeededDir {
+            dir: dir.clone(),
+            method: String::new(),
+            files: 0,
+            bytes: 0,
+            skipped: None,
+            error: None,
+        };
+
+        if !from.is_dir() {
+            seeded.skipped = Some(format!("{} not found in {}", dir, source));
+        } else if to.exists() {
+            seeded.skipped = Some(format!("{} already exists in the worktree", dir));
+        } else {
+            let mut progress = Progress::default();
+            // Start from the cheapest method and only fall back once it stops working
+            let mut method = Method::Reflink;
+            if let Err(e) = seed_dir(&from, &to, &mut method, &mut progress) {
+                seeded.error = Some(format!("Failed to seed {}: {}", dir, e));
+            }
+            seeded.method = match progress.methods.as_slice() {
+                [] => String::new(),
+                [only] => only.as_str().to_string(),
+                _ => "mixed".to_string(),
+            };
+            seeded.files = progress.files;
+            seeded.bytes = progress.bytes;
+            report.total_bytes += progress.bytes;
+        }
+
+        report.dirs.push(seeded);
+    }
+
+    report
+}
+
+fn seed_dir(from: &Path, to: &Path, method: &mut Method, progress: &mut Progress) -> io::Result<()> {
+    fs::create_dir_all(to)?;
+
+    for entry in fs::read_dir(from)? {
+        let entry = entry?;
+        let source = entry.path();
+        let target = to.join(entry.file_name());
+        let file_type = entry.file_type()?;
+
+        if file_type.is_dir() {
+            seed_dir(&source, &target, method, progress)?;
+        } else if file_type.is_symlink() {
+            // Keep links as links, e.g. node_modules/.bin
+            copy_symlink(&source, &target)?;
+        } else {
+            let used = seed_file(&source, &target, method)?;
+            progress.files += 1;
+            progress.bytes += entry.metadata()?.len();
+            if !progress.methods.contains(&used) {
+                progress.methods.push(used);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Place one file, downgrading `method` for the rest of the directory when it fails
+fn seed_file(source: &Path, target: &Path, method: &mut Method) -> io::Result<Method> {
+    if *method == Method::Reflink {
+        if reflink_copy::reflink(source, target).is_ok() {
+            return Ok(Method::Reflink);
+        }
+        // The filesystem can't clone files, or the worktree is on another one
+        *method = Method::Copy;
+    }
+    fs::copy(source, target)?;
+    Ok(Method::Copy)
+}
+
+fn copy_symlink(source: &Path, target: &Path) -> io::Result<()> {
+    let link = fs::read_link(source)?;
+
+    #[cfg(unix)]
+    return std::os::unix::fs::symlink(link, target);
+    #[cfg(windows)]
+    return if source.is_dir() {
+        std::os::windows::fs::symlink_dir(link, target)
+    } else {
+        std::os::windows::fs::symlink_file(link, target)
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support;
+
+    #[test]
+    fn test_seed_caches_reports_sizes_and_skips() {
+        let root = test_support::temp_dir("seed");
+        let source = root.join("source");
+        let target = root.join("target");
+        fs::create_dir_all(source.join("node_modules/pkg")).unwrap();
+        fs::write(source.join("node_modules/pkg/index.js"), "1234").unwrap();
+        fs::write(source.join("node_modules/top.js"), "12").unwrap();
+        fs::create_dir_all(target.join("target")).unwrap();
+        fs::create_dir_all(source.join("target")).unwrap();
+
+        let dirs = vec!["node_modules".to_string(), "target".to_string(), ".venv".to_string()];
+        let report = seed_caches(&source.to_string_lossy(), &target.to_string_lossy(), &dirs);
+
+        assert_eq!(report.dirs[0].files, 2);
+        assert_eq!(report.dirs[0].bytes, 6);
+        assert!(!report.dirs[0].method.is_empty());
+        assert_eq!(fs::read_to_string(target.join("node_modules/pkg/index.js")).unwrap(), "1234");
+        assert!(report.dirs[1].skipped.is_some());
+        assert!(report.dirs[2].skipped.is_some());
+        assert_eq!(report.total_bytes, 6);
+
+        // Seeded files are the worktree's own
+        fs::write(target.join("node_modules/top.js"), "edited").unwrap();
+        assert_eq!(fs::read_to_string(source.join("node_modules/top.js")).unwrap(), "12");
+
+        let _ = fs::remove_dir_all(&root);
+    }
+}
@@ -0,0 +1,70 @@
+use crate::commands::project::ProjectSettings;
+use crate::config::validate_env;
+use crate::layout::{validate_template, validate_worktree_root, WorktreeLayout};
+use crate::store::{AppSettings, ProjectRegistry};
+
+#[tauri::command]
+pub async fn get_worktree_settings() -> Result<AppSettings, String> {
+    AppSettings::load()
+}
+
+#[tauri::command]
+pub async fn update_worktree_settings(settings: AppSettings) -> Result<AppSettings, String> {
+    if let Some(root) = &settings.worktree_root {
+        validate_worktree_root(root)?;
+    }
+    if let Some(template) = &settings.path_template {
+        validate_template(template)?;
+    }
+    validate_env(&settings.env)?;
+
+    settings.save()?;
+    Ok(settings)
+}
+
+#[tauri::command]
+pub async fn update_project_settings(
+    project_id: String,
+    settings: ProjectSettings,
+) -> Result<ProjectSettings, String> {
+    if let Some(root) = &settings.worktree_root {
+        validate_worktree_root(root)?;
+    }
+    if let Some(template) = &settings.path_template {
+        validate_template(template)?;
+    }
+    validate_env(&settings.env)?;
+    if let Some(branches) = &settings.protected_branches {
+        if branches.iter().any(|b| b.trim().is_empty()) {
+            return Err("Protected branch names can't be empty".to_string());
+        }
+    }
+
+    ProjectRegistry::update(|registry| {
+        let project = registry
+            .get_mut(&project_id)
+            .ok_or_else(|| "Project not found".to_string())?;
+        // Only the trust commands approve what `.manymany.toml` runs
+        project.settings = ProjectSettings {
+            trusted_setup: project.settings.trusted_setup.clone(),
+            trusted_terminals: project.settings.trusted_terminals.clone(),
+            trusted_env: project.settings.trusted_env.clone(),
+            ..settings.clone()
+        };
+        Ok(())
+    })?;
+
+    Ok(settings)
+}
+
+/// Show where a worktree would be created with the current settings
+#[tauri::command]
+pub async fn preview_worktree_path(
+    project_path: String,
+    worktree_name: String,
+    branch: String,
+) -> Result<String, String> {
+    let path = WorktreeLayout::for_project(&project_path)?
+        .worktree_path(&project_path, &worktree_name, &branch)?;
+    Ok(path.to_string_lossy().to_string())
+}
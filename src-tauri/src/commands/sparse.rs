@@ -0,0 +1,99 @@
+use serde::{Deserialize, Serialize};
+use std::path::{Component, Path};
+
+use crate::git_commands::git;
+
+/// A worktree's sparse-checkout state
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SparseCheckout {
+    pub enabled: bool,
+    /// Whether the patterns are cone-mode directories rather than gitignore-style patterns
+    pub cone: bool,
+    /// Directories checked out besides the files at the root; empty when not sparse
+    pub paths: Vec<String>,
+}
+
+#[tauri::command]
+pub async fn get_sparse_checkout(worktree_path: String) -> Result<SparseCheckout, String> {
+    read_sparse_checkout(&worktree_path)
+}
+
+/// Check out only `paths` in a worktree, using cone mode. No paths turns sparse checkout off.
+#[tauri::command]
+pub async fn set_sparse_checkout(worktree_path: String, paths: Vec<String>) -> Result<SparseCheckout, String> {
+    if paths.is_empty() {
+        git(&worktree_path, &["sparse-checkout", "disable"])
+            .map_err(|e| format!("Failed to disable sparse checkout: {}", e))?;
+    } else {
+        apply_sparse_checkout(&worktree_path, &paths)?;
+    }
+    read_sparse_checkout(&worktree_path)
+}
+
+/// Set the cone-mode sparse set of a worktree and update its files to match
+pub(crate) fn apply_sparse_checkout(worktree_path: &str, paths: &[String]) -> Result<(), String> {
+    let paths = normalize_sparse_paths(paths)?;
+    let mut args = vec!["sparse-checkout", "set", "--cone", "--"];
+    args.extend(paths.iter().map(|p| p.as_str()));
+
+    git(worktree_path, &args).map_err(|e| format!("Failed to set sparse checkout: {}", e))?;
+    Ok(())
+}
+
+fn read_sparse_checkout(worktree_path: &str) -> Result<SparseCheckout, String> {
+    if !Path::new(worktree_path).exists() {
+        return Err(format!("Worktree does not exist: {}", worktree_path));
+    }
+
+    // Sparse checkout is per worktree, so these come from the worktree's own config
+    let enabled = git(worktree_path, &["config", "--bool", "core.sparseCheckout"]).as_deref() == Ok("true");
+    if !enabled {
+        return Ok(SparseCheckout {
+            enabled: false,
+            cone: false,
+            paths: Vec::new(),
+        });
+    }
+
+    let cone = git(worktree_path, &["config", "--bool", "core.sparseCheckoutCone"]).as_deref() == Ok("true");
+    let paths = git(worktree_path, &["sparse-checkout", "list"])
+        .map_err(|e| format!("Failed to read sparse checkout: {}", e))?
+        .lines()
+        .filter(|l| !l.is_empty())
+        .map(String::from)
+        .collect();
+
+    Ok(SparseCheckout { enabled, cone, paths })
+}
+
+/// Directories relative to the repository root, without `./` or trailing slashes
+pub(crate) fn normalize_sparse_paths(paths: &[String]) -> Result<Vec<String>, String> {
+    let mut normalized = Vec::new();
+    for path in paths {
+        let trimmed = path.trim().trim_start_matches("./").trim_end_matches('/');
+        let relative = Path::new(trimmed);
+        if trimmed.is_empty()
+            || relative.is_absolute()
+            || relative.components().any(|c| matches!(c, Component::ParentDir))
+        {
+            return Err(format!("Sparse checkout paths must be directories inside the repository, got '{}'", path));
+        }
+        if !normalized.iter().any(|p| p == trimmed) {
+            normalized.push(trimmed.to_string());
+        }
+    }
+    Ok(normalized)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize_sparse_paths() {
+        let paths = vec!["./packages/api/".to_string(), "docs".to_string(), "packages/api".to_string()];
+        assert_eq!(normalize_sparse_paths(&paths).unwrap(), vec!["packages/api", "docs"]);
+        assert!(normalize_sparse_paths(&["../other".to_string()]).is_err());
+        assert!(normalize_sparse_paths(&["/".to_string()]).is_err());
+    }
+}
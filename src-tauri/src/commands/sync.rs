@@ -0,0 +1,210 @@
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+use crate::commands::project::get_default_branch;
+use crate::commands::worktree::git_worktree_list;
+use crate::git_commands::{git, upstream_branch};
+use crate::store::{normalize_path, ProjectRegistry};
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct WorktreeSyncResult {
+    pub path: String,
+    pub branch: Option<String>,
+    pub status: String, // "up_to_date", "updated", "conflicted", "skipped" or "failed"
+    /// Files that conflicted; the rebase or merge was aborted, so the worktree is as before
+    pub conflicts: Vec<String>,
+    pub detail: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SyncReport {
+    /// The ref worktrees were synced with, e.g. `origin/main`
+    pub target: String,
+    /// Set when fetching failed; worktrees were synced with what was fetched before
+    pub fetch_error: Option<String>,
+    pub worktrees: Vec<WorktreeSyncResult>,
+}
+
+/// Fetch, then rebase or merge the default branch into every worktree, or the given ones.
+///
+/// Worktrees with uncommitted changes are skipped. A conflict aborts that worktree's
+/// rebase or merge and is reported with the conflicting files.
+#[tauri::command]
+pub async fn sync_worktrees(
+    project_id: String,
+    worktree_paths: Option<Vec<String>>,
+    strategy: Option<String>,
+) -> Result<SyncReport, String> {
+    let project_path = ProjectRegistry::load()?
+        .get(&project_id)
+        .map(|p| p.path.clone())
+        .ok_or_else(|| "Project not found".to_string())?;
+    let rebase = match strategy.as_deref() {
+        None | Some("merge") => false,
+        Some("rebase") => true,
+        Some(other) => return Err(format!("Unknown sync strategy: {}", other)),
+    };
+
+    tokio::task::spawn_blocking(move || {
+        let fetch_error = git(&project_path, &["fetch", "--all", "--prune"]).err();
+        let default_branch = get_default_branch(project_path.clone())?;
+        // The remote branch the default branch integrates with, which needn't be on origin
+        let target = upstream_branch(&project_path, &default_branch)
+            .filter(|upstream| git(&project_path, &["rev-parse", "--verify", "--quiet", upstream]).is_ok())
+            .unwrap_or(default_branch);
+
+        let wanted: Option<Vec<_>> = worktree_paths.map(|paths| paths.iter().map(|p| normalize_path(p)).collect());
+        let worktrees = git_worktree_list(&project_path)?
+            .into_iter()
+            .filter(|e| !e.is_bare)
+            .filter(|e| wanted.as_ref().map(|w| w.contains(&normalize_path(&e.path))).unwrap_or(true))
+            .map(|entry| {
+                let branch = Some(entry.branch).filter(|b| !b.is_empty());
+                let mut result = WorktreeSyncResult {
+                    path: entry.path,
+                    branch,
+                    status: String::new(),
+                    conflicts: Vec::new(),
+                    detail: None,
+                };
+                sync_worktree(&mut result, &target, rebase);
+                result
+            })
+            .collect();
+
+        Ok(SyncReport {
+            target,
+            fetch_error,
+            worktrees,
+        })
+    })
+    .await
+    .map_err(|e| format!("Failed to sync worktrees: {}", e))?
+}
+
+fn sync_worktree(result: &mut WorktreeSyncResult, target: &str, rebase: bool) {
+    let path = result.path.clone();
+    let skip = |result: &mut WorktreeSyncResult, detail: &str| {
+        result.status = "skipped".to_string();
+        result.detail = Some(detail.to_string());
+    };
+
+    if !Path::new(&path).exists() {
+        return skip(result, "Worktree directory is missing");
+    }
+    if result.branch.is_none() {
+        return skip(result, "HEAD is detached");
+    }
+    if in_progress(&path) {
+        return skip(result, "A rebase or merge is already in progress");
+    }
+    match git(&path, &["status", "--porcelain", "--untracked-files=no"]) {
+        Ok(status) if !status.is_empty() => return skip(result, "Uncommitted changes"),
+        Ok(_) => {}
+        Err(e) => {
+            result.status = "failed".to_string();
+            result.detail = Some(e);
+            return;
+        }
+    }
+
+    if git(&path, &["merge-base", "--is-ancestor", target, "HEAD"]).is_ok() {
+        result.status = "up_to_date".to_string();
+        return;
+    }
+
+    let outcome = if rebase {
+        git(&path, &["rebase", target])
+    } else {
+        git(&path, &["merge", "--no-edit", target])
+    };
+    let error = match outcome {
+        Ok(_) => {
+            result.status = "updated".to_string();
+            return;
+        }
+        Err(e) => e,
+    };
+
+    result.conflicts = git(&path, &["diff", "--name-only", "--diff-filter=U", "-z"])
+        .unwrap_or_default()
+        .split('\0')
+        .filter(|f| !f.is_empty())
+        .map(String::from)
+        .collect();
+    // Leave the worktree as it was rather than half-synced
+    let abort = if rebase { "rebase" } else { "merge" };
+    let abort_error = git(&path, &[abort, "--abort"]).err();
+
+    if result.conflicts.is_empty() {
+        result.status = "failed".to_string();
+        result.detail = Some(error);
+    } else {
+        result.status = "conflicted".to_string();
+        result.detail = abort_error.map(|e| format!("Failed to abort the {}: {}", abort, e));
+    }
+}
+
+/// Whether the worktree is in the middle of a rebase or merge
+fn in_progress(worktree_path: &str) -> bool {
+    ["MERGE_HEAD", "rebase-merge", "rebase-apply"].iter().any(|name| {
+        git(worktree_path, &["rev-parse", "--git-path", name])
+            .map(|p| Path::new(worktree_path).join(p).exists())
+            .unwrap_or(false)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{self, commit_file, git as run};
+    use std::fs;
+
+    #[tokio::test]
+    async fn test_sync_worktrees_with_the_default_branch_upstream() {
+        let env = test_support::data_dir();
+        let repo = test_support::init_repo(&env.root);
+        run(&repo, &["remote", "rename", "origin", "upstream"]);
+        let project_id = test_support::add_project(&repo).await;
+        let add = |branch: &str| {
+            run(&repo, &["worktree", "add", "-q", "-b", branch, &format!("../{}", branch)]);
+            env.root.join(branch)
+        };
+
+        add("behind");
+        let clash = add("clash");
+        let clash_head = commit_file(&clash, "a.txt", "clash", "clashing change");
+        let dirty = add("dirty");
+        fs::write(dirty.join("a.txt"), "dirty").unwrap();
+
+        commit_file(&repo, "a.txt", "main", "main moves on");
+        run(&repo, &["push", "-q", "upstream", "main"]);
+        add("fresh");
+
+        let report = sync_worktrees(project_id, None, None).await.unwrap();
+        assert_eq!(report.target, "upstream/main");
+        assert_eq!(report.fetch_error, None);
+
+        let status = |branch: &str| {
+            report
+                .worktrees
+                .iter()
+                .find(|w| w.branch.as_deref() == Some(branch))
+                .unwrap()
+        };
+        assert_eq!(status("main").status, "up_to_date");
+        assert_eq!(status("fresh").status, "up_to_date");
+        assert_eq!(status("behind").status, "updated");
+        assert_eq!(status("dirty").status, "skipped");
+        assert_eq!(status("dirty").detail.as_deref(), Some("Uncommitted changes"));
+        assert_eq!(status("clash").status, "conflicted");
+        assert_eq!(status("clash").conflicts, vec!["a.txt"]);
+
+        // The conflicting merge was aborted
+        assert_eq!(run(&clash, &["rev-parse", "HEAD"]), clash_head);
+        assert!(!in_progress(&clash.to_string_lossy()));
+        assert_eq!(fs::read_to_string(env.root.join("behind/a.txt")).unwrap(), "main");
+
+        let _ = fs::remove_dir_all(&env.root);
+    }
+}
@@ -2,6 +2,7 @@ use serde::{Deserialize, Serialize};
 use tauri::{AppHandle, State};
 use std::sync::Mutex;
 
+use crate::config::ProjectConfig;
 use crate::terminal::{TerminalManager};
 use crate::terminal::task::CreateTerminalRequest;
 
@@ -22,9 +23,11 @@ pub async fn create_terminal(
     app: AppHandle,
     state: State<'_, Mutex<TerminalManager>>,
 ) -> Result<String, String> {
+    // Environment overrides from .manymany.toml and user settings, read before taking the lock
+    let env_overrides = ProjectConfig::load_env(&request.working_directory);
     let terminal_id = {
         let mut manager = state.lock().unwrap();
-        manager.create_terminal(request, app)?
+        manager.create_terminal(request, app, env_overrides)?
     };
     
     Ok(terminal_id)
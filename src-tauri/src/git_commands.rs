@@ -1,22 +1,176 @@
+use serde::{Deserialize, Serialize};
+use std::fs;
 use std::path::Path;
 use std::process::Command;
 
-#[tauri::command]
-pub fn is_git_repository(path: String) -> Result<bool, String> {
-    let path = Path::new(&path);
-    
+use crate::commands::branches::find_remote_branch;
+
+/// What git itself reports about a folder, so bare repos and `.git` files work
+/// the same as a regular checkout
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct RepositoryInfo {
+    /// Absolute path of the repository's common git dir (shared by all worktrees)
+    pub common_dir: String,
+    pub is_bare: bool,
+}
+
+/// Ask git whether `path` is the root of a repository. Returns `None` when it isn't.
+///
+/// Subdirectories of a checkout are rejected, and so is any folder that only sits inside
+/// one, e.g. under a home directory tracked by a dotfiles repo.
+pub fn detect_repository(path: &Path) -> Option<RepositoryInfo> {
+    let (info, git_dir) = containing_repository(path)?;
+    let path_str = path.to_string_lossy();
+
+    let root = if info.is_bare {
+        // The bare repo itself, or a folder whose `.git` points at it (the bare repo + worktrees layout)
+        let dot_git = path.join(".git");
+        let target = match fs::read_to_string(&dot_git) {
+            Ok(content) => content
+                .strip_prefix("gitdir:")
+                .map(|dir| path.join(dir.trim()))
+                .unwrap_or(dot_git),
+            Err(_) => dot_git,
+        };
+        if same_path(&git_dir, &target) {
+            return Some(info);
+        }
+        git_dir
+    } else {
+        git(&path_str, &["rev-parse", "--show-toplevel"]).ok()?
+    };
+
+    if same_path(&root, path) {
+        Some(info)
+    } else {
+        None
+    }
+}
+
+/// The repository `path` is in, wherever in it `path` is, with its absolute git dir
+pub fn containing_repository(path: &Path) -> Option<(RepositoryInfo, String)> {
     if !path.exists() {
-        return Ok(false);
+        return None;
     }
-    
+
+    let output = git(
+        &path.to_string_lossy(),
+        &[
+            "rev-parse",
+            "--path-format=absolute",
+            "--git-common-dir",
+            "--is-bare-repository",
+            "--absolute-git-dir",
+        ],
+    )
+    .ok()?;
+
+    let mut lines = output.lines();
+    let common_dir = lines.next()?.trim().to_string();
+    let is_bare = lines.next()?.trim() == "true";
+    let git_dir = lines.next()?.trim().to_string();
+
+    Some((RepositoryInfo { common_dir, is_bare }, git_dir))
+}
+
+/// Whether `path` is a worktree added with `git worktree add`, rather than the repository itself
+pub fn is_linked_worktree(path: &Path) -> bool {
+    containing_repository(path)
+        .map(|(info, git_dir)| !same_path(&git_dir, &info.common_dir))
+        .unwrap_or(false)
+}
+
+fn same_path(a: impl AsRef<Path>, b: impl AsRef<Path>) -> bool {
+    match (fs::canonicalize(a), fs::canonicalize(b)) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => false,
+    }
+}
+
+/// Run git in `path`, returning stdout without the trailing newline, or stderr on failure
+pub fn git(path: &str, args: &[&str]) -> Result<String, String> {
     let output = Command::new("git")
-        .arg("-C")
-        .arg(path)
-        .arg("rev-parse")
-        .arg("--is-inside-work-tree")
+        .args(&["-C", path])
+        .args(args)
         .output()
-        .map_err(|e| e.to_string())?;
-    
-    Ok(output.status.success())
+        .map_err(|e| format!("Failed to execute git command: {}", e))?;
+
+    if !output.status.success() {
+        return Err(String::from_utf8_lossy(&output.stderr).trim().to_string());
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).trim_end().to_string())
+}
+
+pub fn local_branch_exists(repo_path: &str, branch: &str) -> bool {
+    Command::new("git")
+        .args(&["-C", repo_path, "show-ref", "--verify", "--quiet", &format!("refs/heads/{}", branch)])
+        .status()
+        .map(|status| status.success())
+        .unwrap_or(false)
+}
+
+/// Whether `branch` exists locally or as a remote-tracking branch that `worktree add` can check out
+pub fn branch_exists(repo_path: &str, branch: &str) -> bool {
+    // A name on several remotes exists too; creating the worktree explains how to pick one
+    local_branch_exists(repo_path, branch) || !matches!(find_remote_branch(repo_path, branch), Ok(None))
+}
+
+/// The remote branch `branch` is integrated with, e.g. `upstream/main`: its configured upstream,
+/// or else the remote branch of the same name
+pub fn upstream_branch(repo_path: &str, branch: &str) -> Option<String> {
+    git(
+        repo_path,
+        &["rev-parse", "--abbrev-ref", "--symbolic-full-name", &format!("{}@{{upstream}}", branch)],
+    )
+    .ok()
+    .filter(|upstream| !upstream.is_empty())
+    .or_else(|| find_remote_branch(repo_path, branch).ok().flatten().map(|rb| rb.short_ref()))
+}
+
+#[tauri::command]
+pub fn is_git_repository(path: String) -> Result<bool, String> {
+    Ok(detect_repository(Path::new(&path)).is_some())
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{self, git as run};
+
+    #[test]
+    fn test_detect_repository_only_accepts_the_root() {
+        let root = test_support::temp_dir("detect");
+        let repo = test_support::init_repo(&root);
+        std::fs::create_dir_all(repo.join("src")).unwrap();
+
+        assert!(!detect_repository(&repo).unwrap().is_bare);
+        assert!(detect_repository(&repo.join("src")).is_none());
+        assert!(containing_repository(&repo.join("src")).is_some());
+
+        // A bare repo, and the layout with the bare repo in `.bare` behind a `.git` file
+        assert!(detect_repository(&root.join("origin.git")).unwrap().is_bare);
+        let layout = root.join("layout");
+        run(&root, &["clone", "-q", "--bare", "origin.git", "layout/.bare"]);
+        std::fs::write(layout.join(".git"), "gitdir: ./.bare\n").unwrap();
+        assert!(detect_repository(&layout).unwrap().is_bare);
+        assert!(detect_repository(&layout.join(".bare/refs")).is_none());
+
+        let _ = std::fs::remove_dir_all(root);
+    }
+
+    #[test]
+    fn test_branch_exists_matches_whole_remote_branch_names() {
+        let root = test_support::temp_dir("branch-exists");
+        let repo = test_support::init_repo(&root);
+        run(&repo, &["push", "-q", "origin", "main:feature/foo"]);
+        run(&repo, &["fetch", "-q", "origin"]);
+        let repo = repo.to_string_lossy();
+
+        assert!(branch_exists(&repo, "main"));
+        assert!(branch_exists(&repo, "feature/foo"));
+        assert!(branch_exists(&repo, "origin/feature/foo"));
+        assert!(!branch_exists(&repo, "foo"));
+
+        let _ = std::fs::remove_dir_all(root);
+    }
+}
@@ -0,0 +1,231 @@
+use std::path::{Path, PathBuf};
+
+use crate::config::ProjectConfig;
+use crate::store::normalize_path;
+
+/// Layout used when nothing is configured: `~/.manymany/<project>/<worktree name>`
+pub const DEFAULT_PATH_TEMPLATE: &str = "{root}/{project}/{name}";
+const PLACEHOLDERS: &[&str] = &["root", "project", "hash", "name", "branch"];
+
+pub fn sanitize_project_name(name: &str) -> String {
+    name.chars()
+        .map(|c| match c {
+            '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|' => '_',
+            c if c.is_control() => '_',
+            c => c,
+        })
+        .collect::<String>()
+        .trim_matches('.')
+        .to_string()
+}
+
+/// Check a path template stays under `{root}`, only uses known placeholders and names each
+/// worktree uniquely
+pub fn validate_template(template: &str) -> Result<(), String> {
+    if !template.starts_with("{root}/") {
+        return Err(format!("Path template must start with {{root}}/, got {}", template));
+    }
+    if has_parent_component(template) {
+        return Err(format!("Path template can't contain '..': {}", template));
+    }
+
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        let end = rest[start..]
+            .find('}')
+            .ok_or_else(|| format!("Unclosed placeholder in path template: {}", template))?;
+        let placeholder = &rest[start + 1..start + end];
+        if !PLACEHOLDERS.contains(&placeholder) {
+            return Err(format!(
+                "Unknown placeholder {{{}}} in path template. Available: {}",
+                placeholder,
+                PLACEHOLDERS.iter().map(|p| format!("{{{}}}", p)).collect::<Vec<_>>().join(", ")
+            ));
+        }
+        rest = &rest[start + end + 1..];
+    }
+
+    if !template.contains("{name}") && !template.contains("{branch}") {
+        return Err("Path template must contain {name} or {branch}".to_string());
+    }
+
+    Ok(())
+}
+
+/// Check a configured worktree root is absolute, or relative to `~`
+pub fn validate_worktree_root(root: &str) -> Result<(), String> {
+    if has_parent_component(root) {
+        return Err(format!("Worktree root can't contain '..': {}", root));
+    }
+    expand_home(root).map(|_| ())
+}
+
+fn has_parent_component(path: &str) -> bool {
+    path.split(['/', '\\']).any(|part| part == "..")
+}
+
+/// Where a project's worktrees go, after merging global and per-project settings
+#[derive(Debug, Clone)]
+pub struct WorktreeLayout {
+    pub root: PathBuf,
+    pub template: String,
+}
+
+impl WorktreeLayout {
+    /// Resolve the layout for `project_path` from its effective config
+    pub fn for_project(project_path: &str) -> Result<Self, String> {
+        Self::from_config(&ProjectConfig::load(project_path)?)
+    }
+
+    pub fn from_config(config: &ProjectConfig) -> Result<Self, String> {
+        let root = match &config.worktree_root {
+            Some(root) => expand_home(root)?,
+            None => dirs::home_dir()
+                .ok_or_else(|| "Could not find home directory".to_string())?
+                .join(".manymany"),
+        };
+        let template = config
+            .path_template
+            .clone()
+            .unwrap_or_else(|| DEFAULT_PATH_TEMPLATE.to_string());
+        validate_template(&template)?;
+
+        Ok(Self { root, template })
+    }
+
+    /// Path for a new worktree named `name` on `branch`.
+    ///
+    /// A name with nothing left after sanitizing would resolve to the project's own directory.
+    pub fn worktree_path(
+        &self,
+        project_path: &str,
+        name: &str,
+        branch: &str,
+    ) -> Result<PathBuf, String> {
+        for (placeholder, value) in [("{name}", name), ("{branch}", branch)] {
+            let empty = sanitize_project_name(value).trim().is_empty();
+            if empty && self.template.contains(placeholder) {
+                return Err(format!("'{}' can't be used as a worktree folder name", value));
+            }
+        }
+        Ok(PathBuf::from(self.render(&self.template, project_path, name, branch)))
+    }
+
+    /// The directory that holds all of a project's worktrees, if the template has one.
+    ///
+    /// That's the case when only the last path component depends on the worktree.
+    pub fn project_dir(&self, project_path: &str) -> Option<PathBuf> {
+        let (parent, last) = self.template.rsplit_once('/')?;
+        if parent.contains("{name}") || parent.contains("{branch}") {
+            return None;
+        }
+        if !last.contains("{name}") && !last.contains("{branch}") {
+            return None;
+        }
+        Some(PathBuf::from(self.render(parent, project_path, "", "")))
+    }
+
+    fn render(&self, template: &str, project_path: &str, name: &str, branch: &str) -> String {
+        template
+            .replace("{root}", &self.root.to_string_lossy())
+            .replace("{project}", &project_folder_name(project_path))
+            .replace("{hash}", &path_hash(project_path))
+            .replace("{name}", &sanitize_project_name(name))
+            .replace("{branch}", &sanitize_project_name(branch))
+    }
+}
+
+/// Folder name for a project; `api.git` (a bare clone) gets the same name as an `api` checkout
+fn project_folder_name(project_path: &str) -> String {
+    let name = Path::new(project_path)
+        .file_name()
+        .and_then(|n| n.to_str())
+        .map(|n| n.strip_suffix(".git").filter(|n| !n.is_empty()).unwrap_or(n))
+        .unwrap_or("unknown");
+    sanitize_project_name(name)
+}
+
+/// Short stable hash of the project's location, to tell apart repos with the same name
+fn path_hash(project_path: &str) -> String {
+    // FNV-1a: stable across Rust releases, unlike `DefaultHasher`
+    let path = normalize_path(project_path);
+    let hash = path
+        .to_string_lossy()
+        .bytes()
+        .fold(0xcbf29ce484222325u64, |acc, b| (acc ^ b as u64).wrapping_mul(0x100000001b3));
+    format!("{:08x}", hash as u32)
+}
+
+fn expand_home(path: &str) -> Result<PathBuf, String> {
+    if path == "~" || path.starts_with("~/") {
+        let home_dir = dirs::home_dir()
+            .ok_or_else(|| "Could not find home directory".to_string())?;
+        return Ok(home_dir.join(path.trim_start_matches('~').trim_start_matches('/')));
+    }
+
+    let path = PathBuf::from(path);
+    if !path.is_absolute() {
+        return Err(format!("Worktree root must be an absolute path: {}", path.display()));
+    }
+    Ok(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn layout(template: &str) -> WorktreeLayout {
+        WorktreeLayout {
+            root: PathBuf::from("/wt"),
+            template: template.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_default_template_matches_legacy_layout() {
+        let path = layout(DEFAULT_PATH_TEMPLATE)
+            .worktree_path("/src/api", "fix login", "fix/login")
+            .unwrap();
+        assert_eq!(path, PathBuf::from("/wt/api/fix login"));
+    }
+
+    #[test]
+    fn test_empty_names_dont_resolve_to_the_project_dir() {
+        let layout = layout(DEFAULT_PATH_TEMPLATE);
+        for name in ["", "..", "  ", "..."] {
+            assert!(layout.worktree_path("/src/api", name, "feature").is_err(), "{:?}", name);
+        }
+        // Only placeholders the template uses matter
+        assert!(layout.worktree_path("/src/api", "fix", "..").is_ok());
+    }
+
+    #[test]
+    fn test_hash_separates_repos_with_same_name() {
+        let layout = layout("{root}/{project}-{hash}/{branch}");
+        let a = layout.worktree_path("/src/team-a/api", "x", "feature/x").unwrap();
+        let b = layout.worktree_path("/src/team-b/api", "x", "feature/x").unwrap();
+        assert_ne!(a, b);
+        assert!(a.to_string_lossy().ends_with("/feature_x"));
+        assert_eq!(layout.project_dir("/src/team-a/api"), a.parent().map(Path::to_path_buf));
+    }
+
+    #[test]
+    fn test_validate_template() {
+        assert!(validate_template("{root}/{project}/{name}").is_ok());
+        assert!(validate_template("{root}/{project}").is_err());
+        assert!(validate_template("{root}/{repo}/{name}").is_err());
+        assert!(validate_template("{root}/{name").is_err());
+        assert!(validate_template("{project}/{name}").is_err());
+        assert!(validate_template("/tmp/{name}").is_err());
+        assert!(validate_template("{root}/../{name}").is_err());
+        assert!(validate_template("{root}/{project}/..{name}").is_ok());
+    }
+
+    #[test]
+    fn test_validate_worktree_root() {
+        assert!(validate_worktree_root("/srv/worktrees").is_ok());
+        assert!(validate_worktree_root("~/worktrees").is_ok());
+        assert!(validate_worktree_root("worktrees").is_err());
+        assert!(validate_worktree_root("/srv/../etc").is_err());
+    }
+}
@@ -1,11 +1,32 @@
 mod commands;
+mod config;
 mod git_commands;
+mod layout;
+mod manifest;
+mod store;
 mod terminal;
+#[cfg(test)]
+mod test_support;
+mod workspace;
 
 use commands::{
-    project::{add_project, list_projects, remove_project, get_default_branch, parse_workspace_file, open_in_app},
+    project::{add_project, list_projects, remove_project, get_default_branch, get_project_config, parse_workspace_file, parse_manifest_file, open_in_app},
     worktree::{create_worktree, list_worktrees, remove_worktree, get_available_branches},
+    branches::{list_branches},
+    removal::{check_worktree_removal},
+    lifecycle::{lock_worktree, unlock_worktree, move_worktree, repair_worktrees},
+    bootstrap::{bootstrap_worktree, trust_repo_config, trust_setup_commands},
+    usage::{get_worktree_usage},
+    cleanup::{find_cleanup_candidates, cleanup_worktrees},
+    sync::{sync_worktrees},
+    sparse::{get_sparse_checkout, set_sparse_checkout},
+    review::{create_worktree_from_ref, refresh_worktree_ref},
     git::{get_git_status, git_commit, git_stage_file, git_unstage_file},
+    health::{check_project_health, prune_worktrees, relink_worktree, delete_orphan_directory},
+    settings::{get_worktree_settings, update_worktree_settings, update_project_settings, preview_worktree_path},
+    discovery::{discover_projects, add_projects},
+    bundle::{export_project, import_project},
+    workspace::{create_workspace_worktree, get_workspace_git_status, workspace_stage_files, workspace_commit},
     terminal::{open_editor, create_terminal, write_to_terminal, read_from_terminal, resize_terminal, close_terminal, list_terminals, terminal_input, get_terminal_info, cleanup_terminals},
 };
 use git_commands::{is_git_repository};
@@ -29,7 +50,24 @@ pub fn run() {
             create_worktree,
             list_worktrees,
             remove_worktree,
+            check_worktree_removal,
+            lock_worktree,
+            unlock_worktree,
+            move_worktree,
+            repair_worktrees,
+            bootstrap_worktree,
+            trust_setup_commands,
+            trust_repo_config,
+            get_worktree_usage,
+            find_cleanup_candidates,
+            cleanup_worktrees,
+            sync_worktrees,
+            get_sparse_checkout,
+            set_sparse_checkout,
+            create_worktree_from_ref,
+            refresh_worktree_ref,
             get_available_branches,
+            list_branches,
             get_git_status,
             git_commit,
             git_stage_file,
@@ -47,7 +85,25 @@ pub fn run() {
             is_git_repository,
             get_default_branch,
             parse_workspace_file,
+            parse_manifest_file,
             open_in_app,
+            check_project_health,
+            prune_worktrees,
+            relink_worktree,
+            delete_orphan_directory,
+            get_worktree_settings,
+            update_worktree_settings,
+            update_project_settings,
+            preview_worktree_path,
+            get_project_config,
+            discover_projects,
+            add_projects,
+            export_project,
+            import_project,
+            create_workspace_worktree,
+            get_workspace_git_status,
+            workspace_stage_files,
+            workspace_commit,
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
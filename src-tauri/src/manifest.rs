@@ -0,0 +1,553 @@
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::Reader;
+use serde::Deserialize;
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// Multi-repo manifest formats we can import besides `.code-workspace`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ManifestFormat {
+    /// Google `repo` XML manifest, e.g. `.repo/manifests/default.xml`
+    Repo,
+    GitModules,
+    Mani,
+    /// `.meta` file of the `meta` tool
+    Meta,
+}
+
+impl ManifestFormat {
+    /// Guess the format from the file name. Other XML files only count as `repo` manifests
+    /// where `repo` keeps them, or when their root element is `<manifest>`.
+    pub fn detect(path: &Path) -> Option<Self> {
+        let name = path.file_name()?.to_str()?;
+        match name {
+            ".gitmodules" => Some(Self::GitModules),
+            "mani.yaml" | "mani.yml" => Some(Self::Mani),
+            ".meta" => Some(Self::Meta),
+            "default.xml" => Some(Self::Repo),
+            _ if name.ends_with(".xml") && (in_repo_dir(path) || has_manifest_root(path)) => {
+                Some(Self::Repo)
+            }
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Repo => "repo",
+            Self::GitModules => "gitmodules",
+            Self::Mani => "mani",
+            Self::Meta => "meta",
+        }
+    }
+}
+
+fn in_repo_dir(path: &Path) -> bool {
+    path.ancestors()
+        .skip(1)
+        .any(|dir| dir.file_name().map(|n| n == ".repo").unwrap_or(false))
+}
+
+fn has_manifest_root(path: &Path) -> bool {
+    let content = match fs::read_to_string(path) {
+        Ok(content) => content,
+        Err(_) => return false,
+    };
+    let mut reader = Reader::from_str(&content);
+    loop {
+        match reader.read_event() {
+            Ok(Event::Start(e)) | Ok(Event::Empty(e)) => return e.name().as_ref() == b"manifest",
+            Ok(Event::Eof) | Err(_) => return false,
+            _ => {}
+        }
+    }
+}
+
+/// One repository listed by a manifest
+#[derive(Debug, Clone, PartialEq)]
+pub struct ManifestRepo {
+    pub name: String,
+    pub path: PathBuf,
+    /// Branch the manifest pins the repo to, if it names one
+    pub branch: Option<String>,
+}
+
+/// Read the repositories listed in `path`, resolving their paths against the checkout root
+pub fn load_manifest(path: &Path) -> Result<(ManifestFormat, Vec<ManifestRepo>), String> {
+    let format = ManifestFormat::detect(path)
+        .ok_or_else(|| format!("Unsupported manifest file: {}", path.display()))?;
+    let dir = path.parent().unwrap_or(Path::new("."));
+
+    let repos = match format {
+        ManifestFormat::GitModules => parse_gitmodules(path, dir)?,
+        _ => {
+            let content = fs::read_to_string(path)
+                .map_err(|e| format!("Failed to read manifest: {}", e))?;
+            match format {
+                ManifestFormat::Repo => parse_repo_manifest(&content, &repo_checkout_root(path), &repo_include_dir(path))?,
+                ManifestFormat::Mani => parse_mani(&content, dir)?,
+                _ => parse_meta(&content, dir)?,
+            }
+        }
+    };
+
+    Ok((format, repos))
+}
+
+/// Directory the repos of a manifest are checked out under
+pub fn manifest_root(path: &Path) -> PathBuf {
+    match ManifestFormat::detect(path) {
+        Some(ManifestFormat::Repo) => repo_checkout_root(path),
+        _ => path.parent().unwrap_or(Path::new(".")).to_path_buf(),
+    }
+}
+
+/// Projects in a `repo` manifest are relative to the directory holding `.repo`
+fn repo_checkout_root(manifest_path: &Path) -> PathBuf {
+    manifest_path
+        .ancestors()
+        .find(|dir| dir.file_name().map(|n| n == ".repo").unwrap_or(false))
+        .and_then(Path::parent)
+        .or_else(|| manifest_path.parent())
+        .unwrap_or(Path::new("."))
+        .to_path_buf()
+}
+
+/// `.repo/manifest.xml` includes files from the `.repo/manifests` checkout; any other
+/// manifest includes files next to it
+fn repo_include_dir(manifest_path: &Path) -> PathBuf {
+    let dir = manifest_path.parent().unwrap_or(Path::new("."));
+    if dir.file_name().map(|n| n == ".repo").unwrap_or(false) {
+        dir.join("manifests")
+    } else {
+        dir.to_path_buf()
+    }
+}
+
+/// Includes can nest, but a manifest including itself must not loop forever
+const MAX_INCLUDE_DEPTH: usize = 8;
+
+#[derive(Debug, Default)]
+struct RepoManifest {
+    remote_revisions: HashMap<String, String>,
+    default_remote: Option<String>,
+    default_revision: Option<String>,
+    /// `repo` allows one `<default>` across a manifest and its includes
+    has_default: bool,
+    projects: Vec<RepoProject>,
+}
+
+#[derive(Debug)]
+struct RepoProject {
+    name: String,
+    path: Option<String>,
+    revision: Option<String>,
+    remote: Option<String>,
+}
+
+/// Parse a `repo` manifest, reading `<include>`d files from `include_dir`
+pub fn parse_repo_manifest(content: &str, root: &Path, include_dir: &Path) -> Result<Vec<ManifestRepo>, String> {
+    let mut manifest = RepoManifest::default();
+    read_repo_manifest(content, include_dir, &mut manifest, 0)?;
+
+    let RepoManifest {
+        remote_revisions,
+        default_remote,
+        default_revision,
+        projects,
+        ..
+    } = manifest;
+    Ok(projects
+        .into_iter()
+        .map(|project| {
+            // A project's revision beats its remote's, which beats the manifest default
+            let revision = project
+                .revision
+                .or_else(|| {
+                    project
+                        .remote
+                        .or_else(|| default_remote.clone())
+                        .and_then(|r| remote_revisions.get(&r).cloned())
+                })
+                .or_else(|| default_revision.clone());
+            let path = project.path.unwrap_or_else(|| project.name.clone());
+            ManifestRepo {
+                name: last_component(&project.name),
+                path: root.join(path),
+                branch: revision.and_then(|r| branch_from_revision(&r)),
+            }
+        })
+        .collect())
+}
+
+/// Apply the elements of one manifest file to `manifest`, in document order like `repo` does
+fn read_repo_manifest(
+    content: &str,
+    include_dir: &Path,
+    manifest: &mut RepoManifest,
+    depth: usize,
+) -> Result<(), String> {
+    let mut reader = Reader::from_str(content);
+
+    loop {
+        let event = reader
+            .read_event()
+            .map_err(|e| format!("Failed to parse repo manifest: {}", e))?;
+        let element = match event {
+            Event::Start(e) | Event::Empty(e) => e,
+            Event::Eof => break,
+            _ => continue,
+        };
+
+        let mut attrs = xml_attributes(&element)?;
+        let tag = String::from_utf8_lossy(element.name().as_ref()).to_string();
+        match element.name().as_ref() {
+            b"remote" => {
+                if let (Some(name), Some(revision)) = (attrs.remove("name"), attrs.remove("revision")) {
+                    manifest.remote_revisions.insert(name, revision);
+                }
+            }
+            b"default" => {
+                if manifest.has_default {
+                    return Err(
+                        "Failed to parse repo manifest: duplicate <default> element".to_string(),
+                    );
+                }
+                manifest.has_default = true;
+                manifest.default_remote = attrs.remove("remote");
+                manifest.default_revision = attrs.remove("revision");
+            }
+            b"project" => {
+                let name = required_attr(&mut attrs, &tag, "name")?;
+                manifest.projects.push(RepoProject {
+                    name,
+                    path: attrs.remove("path"),
+                    revision: attrs.remove("revision"),
+                    remote: attrs.remove("remote"),
+                });
+            }
+            b"remove-project" => {
+                let name = required_attr(&mut attrs, &tag, "name")?;
+                let path = attrs.remove("path");
+                manifest.projects.retain(|p| !matches_project(p, &name, path.as_deref()));
+            }
+            b"extend-project" => {
+                let name = required_attr(&mut attrs, &tag, "name")?;
+                let path = attrs.remove("path");
+                let dest_path = attrs.remove("dest-path");
+                let revision = attrs.remove("revision");
+                let remote = attrs.remove("remote");
+                for project in manifest
+                    .projects
+                    .iter_mut()
+                    .filter(|p| matches_project(p, &name, path.as_deref()))
+                {
+                    if dest_path.is_some() {
+                        project.path = dest_path.clone();
+                    }
+                    if revision.is_some() {
+                        project.revision = revision.clone();
+                    }
+                    if remote.is_some() {
+                        project.remote = remote.clone();
+                    }
+                }
+            }
+            b"include" => {
+                let name = required_attr(&mut attrs, &tag, "name")?;
+                if depth >= MAX_INCLUDE_DEPTH {
+                    return Err(format!("Failed to parse repo manifest: includes nested too deeply at {}", name));
+                }
+                let included = fs::read_to_string(include_dir.join(&name))
+                    .map_err(|e| format!("Failed to read included manifest {}: {}", name, e))?;
+                read_repo_manifest(&included, include_dir, manifest, depth + 1)?;
+            }
+            _ => {}
+        }
+    }
+
+    Ok(())
+}
+
+fn required_attr(attrs: &mut HashMap<String, String>, tag: &str, attr: &str) -> Result<String, String> {
+    attrs
+        .remove(attr)
+        .ok_or_else(|| format!("Failed to parse repo manifest: <{}> without a {}", tag, attr))
+}
+
+/// `<remove-project>` and `<extend-project>` match by name, and by path when they give one
+fn matches_project(project: &RepoProject, name: &str, path: Option<&str>) -> bool {
+    project.name == name && path.is_none_or(|path| project.path.as_deref().unwrap_or(&project.name) == path)
+}
+
+fn xml_attributes(element: &BytesStart) -> Result<HashMap<String, String>, String> {
+    let mut attrs = HashMap::new();
+    for attr in element.attributes() {
+        let attr = attr.map_err(|e| format!("Failed to parse repo manifest: {}", e))?;
+        let value = attr
+            .unescape_value()
+            .map_err(|e| format!("Failed to parse repo manifest: {}", e))?;
+        attrs.insert(String::from_utf8_lossy(attr.key.as_ref()).to_string(), value.to_string());
+    }
+    Ok(attrs)
+}
+
+/// Revisions can also be tags or commit ids, which aren't branches to base worktrees on
+fn branch_from_revision(revision: &str) -> Option<String> {
+    if let Some(branch) = revision.strip_prefix("refs/heads/") {
+        return Some(branch.to_string());
+    }
+    let is_commit = revision.len() == 40 && revision.chars().all(|c| c.is_ascii_hexdigit());
+    if revision.starts_with("refs/") || is_commit {
+        return None;
+    }
+    Some(revision.to_string())
+}
+
+/// Submodules, read through `git config` so quoting and includes behave like git's
+fn parse_gitmodules(path: &Path, root: &Path) -> Result<Vec<ManifestRepo>, String> {
+    let output = Command::new("git")
+        .arg("config")
+        .arg("-f")
+        .arg(path)
+        .args(&["--get-regexp", r"^submodule\..*\.(path|branch)$"])
+        .output()
+        .map_err(|e| format!("Failed to execute git command: {}", e))?;
+
+    // git exits with 1 when nothing matches
+    if !output.status.success() && output.status.code() != Some(1) {
+        return Err(format!(
+            "Failed to parse .gitmodules: {}",
+            String::from_utf8_lossy(&output.stderr)
+        ));
+    }
+
+    let mut submodules: Vec<(String, Option<String>, Option<String>)> = Vec::new();
+    for line in String::from_utf8_lossy(&output.stdout).lines() {
+        let (key, value) = match line.split_once(' ') {
+            Some(entry) => entry,
+            None => continue,
+        };
+        // Submodule names may contain dots: submodule.<name>.<key>
+        let (name, key) = match key.strip_prefix("submodule.").and_then(|k| k.rsplit_once('.')) {
+            Some(entry) => entry,
+            None => continue,
+        };
+
+        let index = match submodules.iter().position(|(n, _, _)| n == name) {
+            Some(index) => index,
+            None => {
+                submodules.push((name.to_string(), None, None));
+                submodules.len() - 1
+            }
+        };
+        match key {
+            "path" => submodules[index].1 = Some(value.to_string()),
+            // "." means "same branch as the superproject"
+            "branch" if value != "." => submodules[index].2 = Some(value.to_string()),
+            _ => {}
+        }
+    }
+
+    Ok(submodules
+        .into_iter()
+        .filter_map(|(name, path, branch)| {
+            let path = path?;
+            Some(ManifestRepo {
+                name: last_component(&name),
+                path: root.join(path),
+                branch,
+            })
+        })
+        .collect())
+}
+
+#[derive(Debug, Deserialize)]
+struct ManiFile {
+    #[serde(default)]
+    projects: BTreeMap<String, Option<ManiProject>>,
+}
+
+#[derive(Debug, Deserialize, Default)]
+struct ManiProject {
+    #[serde(default)]
+    path: Option<String>,
+    #[serde(default)]
+    branch: Option<String>,
+}
+
+pub fn parse_mani(content: &str, root: &Path) -> Result<Vec<ManifestRepo>, String> {
+    let file: ManiFile = serde_yaml::from_str(content)
+        .map_err(|e| format!("Failed to parse mani.yaml: {}", e))?;
+
+    Ok(file
+        .projects
+        .into_iter()
+        .map(|(name, project)| {
+            let project = project.unwrap_or_default();
+            let path = project.path.unwrap_or_else(|| name.clone());
+            ManifestRepo {
+                path: root.join(path),
+                branch: project.branch,
+                name,
+            }
+        })
+        .collect())
+}
+
+#[derive(Debug, Deserialize)]
+struct MetaFile {
+    #[serde(default)]
+    projects: BTreeMap<String, String>,
+}
+
+/// `.meta` maps each folder to its clone url
+pub fn parse_meta(content: &str, root: &Path) -> Result<Vec<ManifestRepo>, String> {
+    let file: MetaFile = serde_json::from_str(content)
+        .map_err(|e| format!("Failed to parse .meta: {}", e))?;
+
+    Ok(file
+        .projects
+        .into_keys()
+        .map(|path| ManifestRepo {
+            name: last_component(&path),
+            path: root.join(path),
+            branch: None,
+        })
+        .collect())
+}
+
+fn last_component(name: &str) -> String {
+    name.trim_end_matches('/')
+        .rsplit('/')
+        .next()
+        .unwrap_or(name)
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_repo_manifest() {
+        let content = r#"<?xml version="1.0" encoding="UTF-8"?>
+<manifest>
+  <remote name="origin" fetch=".." revision="refs/heads/develop" />
+  <remote name="aosp" fetch="https://android.googlesource.com" />
+  <default remote="origin" revision="main" />
+  <project name="platform/build" path="build" />
+  <project name="tools/repo" remote="aosp" />
+  <project name="kernel" revision="0123456789abcdef0123456789abcdef01234567">
+    <copyfile src="Makefile" dest="Makefile" />
+  </project>
+</manifest>"#;
+
+        let repos = parse_repo_manifest(content, Path::new("/src"), Path::new("/src/.repo/manifests")).unwrap();
+        assert_eq!(repos[0], ManifestRepo {
+            name: "build".to_string(),
+            path: PathBuf::from("/src/build"),
+            branch: Some("develop".to_string()),
+        });
+        assert_eq!(repos[1].path, PathBuf::from("/src/tools/repo"));
+        assert_eq!(repos[1].branch.as_deref(), Some("main"));
+        assert_eq!(repos[2].branch, None);
+    }
+
+    #[test]
+    fn test_parse_repo_manifest_includes_and_overrides() {
+        let root = crate::test_support::temp_dir("repo-manifest");
+        let manifests = root.join(".repo").join("manifests");
+        fs::create_dir_all(&manifests).unwrap();
+        fs::write(
+            manifests.join("default.xml"),
+            r#"<manifest>
+  <default revision="main" />
+  <project name="platform/build" path="build" />
+  <project name="tools/repo" />
+  <project name="docs" />
+</manifest>"#,
+        )
+        .unwrap();
+        fs::write(
+            root.join(".repo").join("manifest.xml"),
+            r#"<manifest>
+  <include name="default.xml" />
+  <remove-project name="docs" />
+  <extend-project name="platform/build" dest-path="src/build" revision="refs/heads/stable" />
+  <extend-project name="tools/repo" path="elsewhere" revision="ignored" />
+</manifest>"#,
+        )
+        .unwrap();
+
+        let (format, repos) = load_manifest(&root.join(".repo").join("manifest.xml")).unwrap();
+        assert_eq!(format, ManifestFormat::Repo);
+        assert_eq!(repos, vec![
+            ManifestRepo {
+                name: "build".to_string(),
+                path: root.join("src/build"),
+                branch: Some("stable".to_string()),
+            },
+            ManifestRepo {
+                name: "repo".to_string(),
+                path: root.join("tools/repo"),
+                branch: Some("main".to_string()),
+            },
+        ]);
+
+        // Missing includes and self-includes are errors rather than silently empty
+        let missing = r#"<manifest><include name="missing.xml" /></manifest>"#;
+        assert!(parse_repo_manifest(missing, &root, &manifests).is_err());
+        fs::write(manifests.join("loop.xml"), r#"<manifest><include name="loop.xml" /></manifest>"#).unwrap();
+        assert!(load_manifest(&manifests.join("loop.xml")).is_err());
+
+        let _ = fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn test_detect_only_takes_repo_manifests_for_xml() {
+        let root = crate::test_support::temp_dir("detect");
+        fs::write(root.join("pom.xml"), "<?xml version=\"1.0\"?>\n<project></project>").unwrap();
+        fs::write(root.join("team.xml"), "<!-- ours -->\n<manifest></manifest>").unwrap();
+
+        assert_eq!(ManifestFormat::detect(&root.join("pom.xml")), None);
+        assert_eq!(ManifestFormat::detect(&root.join("team.xml")), Some(ManifestFormat::Repo));
+        assert_eq!(ManifestFormat::detect(&root.join("default.xml")), Some(ManifestFormat::Repo));
+        let in_repo = root.join(".repo/manifests/release.xml");
+        assert_eq!(ManifestFormat::detect(&in_repo), Some(ManifestFormat::Repo));
+
+        let _ = fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn test_repo_manifest_allows_one_default() {
+        let root = crate::test_support::temp_dir("repo-default");
+        let base = r#"<manifest><default revision="main" /></manifest>"#;
+        fs::write(root.join("base.xml"), base).unwrap();
+        let manifest = r#"<manifest>
+  <default revision="develop" />
+  <include name="base.xml" />
+</manifest>"#;
+
+        let error = parse_repo_manifest(manifest, &root, &root).unwrap_err();
+        assert!(error.contains("duplicate <default>"));
+
+        let _ = fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn test_parse_mani_and_meta() {
+        let mani = "projects:\n  api:\n    path: services/api\n    branch: dev\n  web:\n";
+        let repos = parse_mani(mani, Path::new("/src")).unwrap();
+        assert_eq!(repos[0].path, PathBuf::from("/src/services/api"));
+        assert_eq!(repos[0].branch.as_deref(), Some("dev"));
+        assert_eq!(repos[1].path, PathBuf::from("/src/web"));
+
+        let meta = r#"{"projects": {"libs/core": "git@example.com:core.git"}}"#;
+        let repos = parse_meta(meta, Path::new("/src")).unwrap();
+        assert_eq!(repos[0].name, "core");
+        assert_eq!(repos[0].path, PathBuf::from("/src/libs/core"));
+    }
+}
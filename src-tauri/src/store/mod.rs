@@ -0,0 +1,140 @@
+pub mod projects;
+pub mod settings;
+pub mod worktrees;
+
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+pub use projects::ProjectRegistry;
+pub use settings::AppSettings;
+pub use worktrees::{SourceRef, WorktreeRecord, WorktreeStore};
+
+/// Same identifier as `tauri.conf.json`, so our files live next to Tauri's own app data
+const APP_IDENTIFIER: &str = "com.jasonzhou.manymany";
+
+/// Directory holding the backend's persistent state.
+///
+/// `MANYMANY_DATA_DIR` overrides the location, which lets a CLI or a test run
+/// point at a different registry without touching the user's real one.
+pub fn app_data_dir() -> Result<PathBuf, String> {
+    if let Ok(dir) = std::env::var("MANYMANY_DATA_DIR") {
+        if !dir.is_empty() {
+            return Ok(PathBuf::from(dir));
+        }
+    }
+
+    dirs::data_dir()
+        .map(|dir| dir.join(APP_IDENTIFIER))
+        .ok_or_else(|| "Could not find app data directory".to_string())
+}
+
+/// Write a file so readers only ever see the old or the new contents.
+///
+/// The data goes to a temporary file in the same directory, is flushed to disk
+/// and then renamed over the destination.
+pub fn write_atomic(path: &Path, contents: &[u8]) -> Result<(), String> {
+    let parent = path
+        .parent()
+        .ok_or_else(|| format!("Invalid store path: {}", path.display()))?;
+    fs::create_dir_all(parent)
+        .map_err(|e| format!("Failed to create data directory: {}", e))?;
+
+    let file_name = path
+        .file_name()
+        .and_then(|n| n.to_str())
+        .unwrap_or("store");
+    let tmp_path = parent.join(format!(".{}.{}.tmp", file_name, std::process::id()));
+
+    let result = (|| {
+        let mut file = fs::File::create(&tmp_path)?;
+        file.write_all(contents)?;
+        file.sync_all()?;
+        fs::rename(&tmp_path, path)
+    })();
+
+    if let Err(e) = result {
+        let _ = fs::remove_file(&tmp_path);
+        return Err(format!("Failed to write {}: {}", path.display(), e));
+    }
+
+    Ok(())
+}
+
+/// Exclusive lock on a store file, released when dropped.
+///
+/// The in-process mutexes only order threads of one app; this is an OS file lock,
+/// so a second instance or a CLI updating the same file waits its turn too. The OS
+/// releases it if the holder crashes, so there are no stale locks to clean up.
+pub struct StoreLock {
+    _file: fs::File,
+}
+
+/// Block until this process holds the lock for `path`.
+///
+/// The lock is taken on a `.<name>.lock` file next to it, since `write_atomic`
+/// replaces the store file itself.
+pub fn lock_store(path: &Path) -> Result<StoreLock, String> {
+    let parent = path
+        .parent()
+        .ok_or_else(|| format!("Invalid store path: {}", path.display()))?;
+    fs::create_dir_all(parent)
+        .map_err(|e| format!("Failed to create data directory: {}", e))?;
+
+    let file_name = path
+        .file_name()
+        .and_then(|n| n.to_str())
+        .unwrap_or("store");
+    let lock_path = parent.join(format!(".{}.lock", file_name));
+    let file = OpenOptions::new()
+        .create(true)
+        .truncate(false)
+        .write(true)
+        .open(&lock_path)
+        .map_err(|e| format!("Failed to open {}: {}", lock_path.display(), e))?;
+    file.lock()
+        .map_err(|e| format!("Failed to lock {}: {}", path.display(), e))?;
+
+    Ok(StoreLock { _file: file })
+}
+
+/// Canonical form of a path for comparisons; falls back to the raw path once it's gone
+pub fn normalize_path(path: &str) -> PathBuf {
+    Path::new(path)
+        .canonicalize()
+        .unwrap_or_else(|_| PathBuf::from(path.trim_end_matches('/')))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    #[test]
+    fn test_lock_store_excludes_other_handles() {
+        let root = crate::test_support::temp_dir("store-lock");
+        let path = root.join("projects.json");
+        let held = lock_store(&path).unwrap();
+
+        // Each call opens its own handle, just like another process would
+        let acquired = Arc::new(AtomicBool::new(false));
+        let waiter = {
+            let path = path.clone();
+            let acquired = acquired.clone();
+            std::thread::spawn(move || {
+                let _lock = lock_store(&path).unwrap();
+                acquired.store(true, Ordering::SeqCst);
+            })
+        };
+
+        std::thread::sleep(Duration::from_millis(200));
+        assert!(!acquired.load(Ordering::SeqCst));
+        drop(held);
+        waiter.join().unwrap();
+        assert!(acquired.load(Ordering::SeqCst));
+
+        let _ = fs::remove_dir_all(&root);
+    }
+}
@@ -0,0 +1,172 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+use crate::commands::project::Project;
+use crate::store::{app_data_dir, lock_store, normalize_path, write_atomic};
+
+/// Current on-disk format of `projects.json`
+pub const SCHEMA_VERSION: u32 = 1;
+
+lazy_static::lazy_static! {
+    /// Serializes read-modify-write cycles on the registry within this process
+    static ref REGISTRY_LOCK: Mutex<()> = Mutex::new(());
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct RegistryFile {
+    version: u32,
+    projects: Vec<Project>,
+}
+
+/// The list of registered projects, backed by `projects.json` in the app data dir
+#[derive(Debug)]
+pub struct ProjectRegistry {
+    path: PathBuf,
+    projects: Vec<Project>,
+}
+
+impl ProjectRegistry {
+    /// Location of the registry file
+    pub fn default_path() -> Result<PathBuf, String> {
+        Ok(app_data_dir()?.join("projects.json"))
+    }
+
+    /// Read the registry, migrating older formats. A missing file is an empty registry.
+    pub fn load() -> Result<Self, String> {
+        Self::load_from(Self::default_path()?)
+    }
+
+    pub fn load_from(path: PathBuf) -> Result<Self, String> {
+        if !path.exists() {
+            return Ok(Self { path, projects: Vec::new() });
+        }
+
+        let content = fs::read_to_string(&path)
+            .map_err(|e| format!("Failed to read project registry: {}", e))?;
+        let value: Value = serde_json::from_str(&content)
+            .map_err(|e| format!("Failed to parse project registry: {}", e))?;
+        let file = migrate(value)?;
+
+        Ok(Self { path, projects: file.projects })
+    }
+
+    /// Load the registry, apply `f` and save the result, holding the registry lock throughout.
+    ///
+    /// The file lock makes other processes' updates wait too, so none of them is lost.
+    pub fn update<T>(f: impl FnOnce(&mut Self) -> Result<T, String>) -> Result<T, String> {
+        let _guard = REGISTRY_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        let path = Self::default_path()?;
+        let _file_lock = lock_store(&path)?;
+        let mut registry = Self::load_from(path)?;
+        let result = f(&mut registry)?;
+        registry.save()?;
+        Ok(result)
+    }
+
+    pub fn save(&self) -> Result<(), String> {
+        let file = RegistryFile {
+            version: SCHEMA_VERSION,
+            projects: self.projects.clone(),
+        };
+        let json = serde_json::to_string_pretty(&file)
+            .map_err(|e| format!("Failed to serialize project registry: {}", e))?;
+        write_atomic(&self.path, json.as_bytes())
+    }
+
+    pub fn projects(&self) -> &[Project] {
+        &self.projects
+    }
+
+    pub fn get(&self, id: &str) -> Option<&Project> {
+        self.projects.iter().find(|p| p.id == id)
+    }
+
+    pub fn get_mut(&mut self, id: &str) -> Option<&mut Project> {
+        self.projects.iter_mut().find(|p| p.id == id)
+    }
+
+    /// Find a project by its path, comparing canonical paths where possible
+    pub fn find_by_path(&self, path: &str) -> Option<&Project> {
+        let wanted = normalize_path(path);
+        self.projects.iter().find(|p| normalize_path(&p.path) == wanted)
+    }
+
+    pub fn insert(&mut self, project: Project) -> Result<(), String> {
+        if let Some(existing) = self.find_by_path(&project.path) {
+            return Err(format!(
+                "This folder is already registered as project '{}'",
+                existing.name
+            ));
+        }
+        self.projects.push(project);
+        Ok(())
+    }
+
+    pub fn remove(&mut self, id: &str) -> Option<Project> {
+        let index = self.projects.iter().position(|p| p.id == id)?;
+        Some(self.projects.remove(index))
+    }
+}
+
+/// Bring a registry file of any known version up to `SCHEMA_VERSION`.
+///
+/// Version 0 is the unversioned format: a bare JSON array of projects.
+fn migrate(mut value: Value) -> Result<RegistryFile, String> {
+    if value.is_array() {
+        value = serde_json::json!({ "version": 0, "projects": value });
+    }
+
+    let version = value
+        .get("version")
+        .and_then(|v| v.as_u64())
+        .ok_or_else(|| "Project registry has no schema version".to_string())? as u32;
+
+    if version > SCHEMA_VERSION {
+        return Err(format!(
+            "Project registry was written by a newer version of ManyMany (schema {}, expected {})",
+            version, SCHEMA_VERSION
+        ));
+    }
+
+    if version == 0 {
+        // v0 entries may predate `worktrees` and `created_at`
+        if let Some(projects) = value.get_mut("projects").and_then(|p| p.as_array_mut()) {
+            for project in projects.iter_mut().filter_map(|p| p.as_object_mut()) {
+                project.entry("worktrees").or_insert_with(|| Value::Array(Vec::new()));
+                project
+                    .entry("created_at")
+                    .or_insert_with(|| Value::String(chrono::Utc::now().to_rfc3339()));
+            }
+        }
+        value["version"] = Value::from(1);
+    }
+
+    serde_json::from_value(value).map_err(|e| format!("Invalid project registry: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_migrate_unversioned_array() {
+        let legacy = serde_json::json!([
+            { "id": "1", "name": "api", "path": "/tmp/api", "project_type": "repository" }
+        ]);
+
+        let file = migrate(legacy).unwrap();
+        assert_eq!(file.version, 1);
+        assert_eq!(file.projects.len(), 1);
+        assert!(file.projects[0].worktrees.is_empty());
+        assert!(file.projects[0].default_branch.is_none());
+    }
+
+    #[test]
+    fn test_migrate_rejects_newer_schema() {
+        let future = serde_json::json!({ "version": SCHEMA_VERSION + 1, "projects": [] });
+        assert!(migrate(future).is_err());
+    }
+}
@@ -0,0 +1,77 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+use crate::store::{app_data_dir, lock_store, write_atomic};
+
+/// Current on-disk format of `settings.json`
+pub const SCHEMA_VERSION: u32 = 1;
+
+lazy_static::lazy_static! {
+    static ref SETTINGS_LOCK: Mutex<()> = Mutex::new(());
+}
+
+/// User-level backend settings; each project can override them in its registry entry
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct AppSettings {
+    /// Directory new worktrees go under. Defaults to `~/.manymany`.
+    #[serde(default)]
+    pub worktree_root: Option<String>,
+    /// Where a worktree goes inside the root, e.g. `{root}/{project}-{hash}/{branch}`
+    #[serde(default)]
+    pub path_template: Option<String>,
+    /// Environment for terminals and apps started from any worktree
+    #[serde(default)]
+    pub env: BTreeMap<String, String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct SettingsFile {
+    version: u32,
+    #[serde(flatten)]
+    settings: AppSettings,
+}
+
+impl AppSettings {
+    fn path() -> Result<PathBuf, String> {
+        Ok(app_data_dir()?.join("settings.json"))
+    }
+
+    /// Read the settings. A missing file means defaults.
+    pub fn load() -> Result<Self, String> {
+        let path = Self::path()?;
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+
+        let content = fs::read_to_string(&path)
+            .map_err(|e| format!("Failed to read settings: {}", e))?;
+        let file: SettingsFile = serde_json::from_str(&content)
+            .map_err(|e| format!("Failed to parse settings: {}", e))?;
+
+        if file.version > SCHEMA_VERSION {
+            return Err(format!(
+                "Settings were written by a newer version of ManyMany (schema {}, expected {})",
+                file.version, SCHEMA_VERSION
+            ));
+        }
+
+        Ok(file.settings)
+    }
+
+    /// Write the settings, taking the same cross-process lock as the other stores
+    pub fn save(&self) -> Result<(), String> {
+        let _guard = SETTINGS_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        let path = Self::path()?;
+        let _file_lock = lock_store(&path)?;
+        let file = SettingsFile {
+            version: SCHEMA_VERSION,
+            settings: self.clone(),
+        };
+        let json = serde_json::to_string_pretty(&file)
+            .map_err(|e| format!("Failed to serialize settings: {}", e))?;
+        write_atomic(&path, json.as_bytes())
+    }
+}
@@ -0,0 +1,150 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+use crate::store::{app_data_dir, lock_store, normalize_path, write_atomic};
+
+/// Current on-disk format of the per-project worktree files
+pub const SCHEMA_VERSION: u32 = 1;
+
+lazy_static::lazy_static! {
+    static ref WORKTREE_STORE_LOCK: Mutex<()> = Mutex::new(());
+}
+
+/// A ref fetched from a remote, e.g. a pull request's `refs/pull/123/head`
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct SourceRef {
+    pub remote: String,
+    pub ref_name: String,
+}
+
+/// What we know about a worktree beyond what `git worktree list` reports
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct WorktreeRecord {
+    pub id: String,
+    pub path: String,
+    pub branch: String,
+    /// Name of the worktree's admin dir under `<git-common-dir>/worktrees/`.
+    /// Git keeps it across `git worktree move`, so it identifies a moved worktree.
+    #[serde(default)]
+    pub git_name: Option<String>,
+    #[serde(default)]
+    pub base_ref: Option<String>,
+    /// Commit the branch pointed at when it was created for this worktree; its own work follows it
+    #[serde(default)]
+    pub base_commit: Option<String>,
+    #[serde(default)]
+    pub display_name: Option<String>,
+    #[serde(default)]
+    pub created_by: Option<String>,
+    pub created_at: String,
+    /// Found through `git worktree list` rather than created by us
+    #[serde(default)]
+    pub adopted: bool,
+    /// Where the branch was fetched from, so it can be fetched again
+    #[serde(default)]
+    pub source_ref: Option<SourceRef>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct WorktreeStoreFile {
+    version: u32,
+    worktrees: Vec<WorktreeRecord>,
+}
+
+/// Worktree metadata for one project, stored in `worktrees/<project_id>.json`
+#[derive(Debug)]
+pub struct WorktreeStore {
+    path: PathBuf,
+    records: Vec<WorktreeRecord>,
+}
+
+impl WorktreeStore {
+    fn path_for(project_id: &str) -> Result<PathBuf, String> {
+        if project_id.is_empty()
+            || !project_id
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+        {
+            return Err(format!("Invalid project id: {}", project_id));
+        }
+        Ok(app_data_dir()?
+            .join("worktrees")
+            .join(format!("{}.json", project_id)))
+    }
+
+    pub fn load(project_id: &str) -> Result<Self, String> {
+        let path = Self::path_for(project_id)?;
+        if !path.exists() {
+            return Ok(Self { path, records: Vec::new() });
+        }
+
+        let content = fs::read_to_string(&path)
+            .map_err(|e| format!("Failed to read worktree metadata: {}", e))?;
+        let value: Value = serde_json::from_str(&content)
+            .map_err(|e| format!("Failed to parse worktree metadata: {}", e))?;
+
+        let version = value.get("version").and_then(|v| v.as_u64()).unwrap_or(0) as u32;
+        if version > SCHEMA_VERSION {
+            return Err(format!(
+                "Worktree metadata was written by a newer version of ManyMany (schema {}, expected {})",
+                version, SCHEMA_VERSION
+            ));
+        }
+
+        let file: WorktreeStoreFile = serde_json::from_value(value)
+            .map_err(|e| format!("Invalid worktree metadata: {}", e))?;
+
+        Ok(Self { path, records: file.worktrees })
+    }
+
+    /// Load the store for `project_id`, apply `f` and save, holding the store lock throughout
+    pub fn update<T>(
+        project_id: &str,
+        f: impl FnOnce(&mut Self) -> Result<T, String>,
+    ) -> Result<T, String> {
+        let _guard = WORKTREE_STORE_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        let _file_lock = lock_store(&Self::path_for(project_id)?)?;
+        let mut store = Self::load(project_id)?;
+        let result = f(&mut store)?;
+        store.save()?;
+        Ok(result)
+    }
+
+    pub fn save(&self) -> Result<(), String> {
+        let file = WorktreeStoreFile {
+            version: SCHEMA_VERSION,
+            worktrees: self.records.clone(),
+        };
+        let json = serde_json::to_string_pretty(&file)
+            .map_err(|e| format!("Failed to serialize worktree metadata: {}", e))?;
+        write_atomic(&self.path, json.as_bytes())
+    }
+
+    pub fn records_mut(&mut self) -> &mut Vec<WorktreeRecord> {
+        &mut self.records
+    }
+
+    /// Add a record, replacing any existing record for the same path
+    pub fn upsert(&mut self, record: WorktreeRecord) {
+        let path = normalize_path(&record.path);
+        self.records.retain(|r| normalize_path(&r.path) != path);
+        self.records.push(record);
+    }
+
+    pub fn find_by_path(&self, path: &str) -> Option<&WorktreeRecord> {
+        let wanted = normalize_path(path);
+        self.records.iter().find(|r| normalize_path(&r.path) == wanted)
+    }
+
+    pub fn remove_by_path(&mut self, path: &str) -> Option<WorktreeRecord> {
+        let wanted = normalize_path(path);
+        let index = self
+            .records
+            .iter()
+            .position(|r| normalize_path(&r.path) == wanted)?;
+        Some(self.records.remove(index))
+    }
+}
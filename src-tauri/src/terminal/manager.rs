@@ -1,4 +1,5 @@
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use tokio::sync::mpsc;
 use tokio::task::JoinHandle;
@@ -8,6 +9,7 @@ use uuid::Uuid;
 use crate::terminal::task::{TerminalTask, CreateTerminalRequest};
 use crate::terminal::task::terminal_task as run_terminal_task;
 use crate::terminal::environment::EnvironmentInfo;
+use crate::store::normalize_path;
 
 #[derive(Debug)]
 pub struct TerminalManager {
@@ -51,11 +53,12 @@ impl TerminalManager {
         }
     }
 
-    /// Create a new terminal with async streaming
+    /// Create a new terminal with async streaming; `env_overrides` come from the project config
     pub fn create_terminal(
         &mut self,
         request: CreateTerminalRequest,
         app: AppHandle,
+        env_overrides: BTreeMap<String, String>,
     ) -> Result<String, String> {
         let terminal_id = Uuid::new_v4().to_string();
         
@@ -75,7 +78,7 @@ impl TerminalManager {
         let task_terminal_id = terminal_id.clone();
         let env_info_clone = self.env_info.clone();
         let handle = tokio::spawn(async move {
-            run_terminal_task(task_terminal_id, request, input_rx, app, env_info_clone).await
+            run_terminal_task(task_terminal_id, request, input_rx, app, env_info_clone, env_overrides).await
         });
         
         // Store terminal and task
@@ -125,6 +128,38 @@ impl TerminalManager {
         self.terminals.contains_key(terminal_id)
     }
 
+    /// Terminals inside `from`, with their working directory relative to it.
+    ///
+    /// Call this before `from` moves: afterwards it can't be resolved through symlinks.
+    pub fn plan_relocation(&self, from: &str) -> Vec<(String, PathBuf)> {
+        self.terminals
+            .values()
+            .filter_map(|terminal| {
+                let rest = relative_to(&terminal.working_directory, from)?;
+                Some((terminal.id.clone(), rest))
+            })
+            .collect()
+    }
+
+    /// Point the planned terminals at the same place under `to`, after a worktree moved.
+    /// Returns the ids of the terminals that were updated.
+    pub fn apply_relocation(&mut self, planned: Vec<(String, PathBuf)>, to: &str) -> Vec<String> {
+        let mut relocated = Vec::new();
+
+        for (id, rest) in planned {
+            if let Some(terminal) = self.terminals.get_mut(&id) {
+                terminal.working_directory = if rest.as_os_str().is_empty() {
+                    to.to_string()
+                } else {
+                    Path::new(to).join(rest).to_string_lossy().to_string()
+                };
+                relocated.push(id);
+            }
+        }
+
+        relocated
+    }
+
     /// Get terminal count
     pub fn terminal_count(&self) -> usize {
         self.terminals.len()
@@ -156,4 +191,57 @@ impl Default for TerminalManager {
     fn default() -> Self {
         Self::new()
     }
-}
\ No newline at end of file
+}
+
+/// `dir` relative to `base`, if it is `base` or inside it.
+///
+/// Paths are compared by whole components, so `/wt/api-v2` isn't inside `/wt/api`.
+fn relative_to(dir: &str, base: &str) -> Option<PathBuf> {
+    normalize_path(dir)
+        .strip_prefix(normalize_path(base))
+        .ok()
+        .map(Path::to_path_buf)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_relative_to_matches_whole_components() {
+        assert_eq!(relative_to("/wt/api", "/wt/api"), Some(PathBuf::new()));
+        assert_eq!(relative_to("/wt/api/src", "/wt/api/"), Some(PathBuf::from("src")));
+        assert_eq!(relative_to("/wt/api-v2", "/wt/api"), None);
+        assert_eq!(relative_to("/wt", "/wt/api"), None);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_relocation_is_planned_before_the_move() {
+        let root = crate::test_support::temp_dir("relocate");
+        std::fs::create_dir_all(root.join("real/src")).unwrap();
+        // The terminal was opened through a symlink, the move names the real directory
+        std::os::unix::fs::symlink(root.join("real"), root.join("link")).unwrap();
+
+        let mut manager = TerminalManager::new();
+        let (input_tx, _input_rx) = mpsc::unbounded_channel();
+        let working_directory = root.join("link/src").to_string_lossy().to_string();
+        let terminal = TerminalTask::new(
+            "t1".to_string(),
+            "shell".to_string(),
+            "w".to_string(),
+            working_directory,
+            input_tx,
+        );
+        manager.terminals.insert("t1".to_string(), terminal);
+
+        let from = root.join("real");
+        let planned = manager.plan_relocation(&from.to_string_lossy());
+        std::fs::rename(&from, root.join("moved")).unwrap();
+        let to = root.join("moved").to_string_lossy().to_string();
+        assert_eq!(manager.apply_relocation(planned, &to), vec!["t1"]);
+        assert_eq!(manager.terminals["t1"].working_directory, format!("{}/src", to));
+
+        let _ = std::fs::remove_dir_all(&root);
+    }
+}
@@ -1,3 +1,4 @@
+use std::collections::BTreeMap;
 use std::io::Write;
 use std::sync::Arc;
 use tokio::sync::mpsc;
@@ -56,6 +57,7 @@ pub async fn terminal_task(
     input_rx: mpsc::UnboundedReceiver<String>,
     app: AppHandle,
     env_info: Arc<EnvironmentInfo>,
+    env_overrides: BTreeMap<String, String>,
 ) -> Result<(), String> {
     
     // Create PTY system
@@ -100,6 +102,11 @@ pub async fn terminal_task(
         cmd.env(key, value);
     }
     
+    // Project environment overrides win over the detected environment
+    for (key, value) in env_overrides {
+        cmd.env(key, value);
+    }
+    
     // Validate working directory
     let working_dir = std::path::Path::new(&request.working_directory);
     if !working_dir.exists() {
@@ -0,0 +1,93 @@
+//! Helpers for tests that need real git repositories or the app's data directory
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, MutexGuard};
+
+lazy_static::lazy_static! {
+    static ref ENV_LOCK: Mutex<()> = Mutex::new(());
+}
+
+/// A fresh directory under the system temp dir
+pub fn temp_dir(prefix: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("manymany-{}-{}", prefix, uuid::Uuid::new_v4()));
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+/// Run git in `dir`, panicking when it fails
+pub fn git(dir: &Path, args: &[&str]) -> String {
+    crate::git_commands::git(&dir.to_string_lossy(), args)
+        .unwrap_or_else(|e| panic!("git {:?} failed: {}", args, e))
+}
+
+pub fn commit_file(repo: &Path, file: &str, content: &str, message: &str) -> String {
+    let path = repo.join(file);
+    fs::create_dir_all(path.parent().unwrap()).unwrap();
+    fs::write(&path, content).unwrap();
+    git(repo, &["add", file]);
+    git(repo, &["commit", "-q", "-m", message]);
+    git(repo, &["rev-parse", "HEAD"])
+}
+
+/// `<root>/origin.git` and a clone of it at `<root>/repo` with one commit on `main`
+pub fn init_repo(root: &Path) -> PathBuf {
+    let origin = root.join("origin.git");
+    let repo = root.join("repo");
+    git(
+        root,
+        &[
+            "init",
+            "-q",
+            "--bare",
+            "-b",
+            "main",
+            &origin.to_string_lossy(),
+        ],
+    );
+    git(root, &["init", "-q", "-b", "main", &repo.to_string_lossy()]);
+    git(&repo, &["config", "user.name", "Test"]);
+    git(&repo, &["config", "user.email", "test@example.com"]);
+    git(
+        &repo,
+        &["remote", "add", "origin", &origin.to_string_lossy()],
+    );
+    commit_file(&repo, "a.txt", "a", "initial");
+    git(&repo, &["push", "-q", "-u", "origin", "main"]);
+    git(&repo, &["remote", "set-head", "origin", "main"]);
+    repo
+}
+
+/// Points the registry, settings and `~` at a temp dir until dropped.
+///
+/// Tests sharing the process environment take turns through a lock.
+pub struct DataDir {
+    pub root: PathBuf,
+    _guard: MutexGuard<'static, ()>,
+}
+
+pub fn data_dir() -> DataDir {
+    let guard = ENV_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let root = temp_dir("data");
+    std::env::set_var("MANYMANY_DATA_DIR", root.join("data"));
+    std::env::set_var("HOME", root.join("home"));
+    DataDir {
+        root,
+        _guard: guard,
+    }
+}
+
+/// Register `path` as a repository project and return its id
+pub async fn add_project(path: &Path) -> String {
+    let request = crate::commands::project::AddProjectRequest {
+        name: path.file_name().unwrap().to_string_lossy().to_string(),
+        path: path.to_string_lossy().to_string(),
+        project_type: "repository".to_string(),
+        default_branch: None,
+        workspace_repos: None,
+    };
+    crate::commands::project::add_project(request)
+        .await
+        .unwrap()
+        .id
+}
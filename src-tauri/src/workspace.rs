@@ -0,0 +1,222 @@
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::manifest::{load_manifest, ManifestFormat};
+
+/// A VS Code `.code-workspace` file
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct WorkspaceFile {
+    #[serde(default)]
+    pub folders: Vec<WorkspaceFolder>,
+    #[serde(default, skip_serializing_if = "Map::is_empty")]
+    pub settings: Map<String, Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub extensions: Option<WorkspaceExtensions>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub launch: Option<WorkspaceLaunch>,
+    /// Sections we don't model (`tasks`, `remoteAuthority`, ...), kept so the file can be written back
+    #[serde(flatten)]
+    pub other: Map<String, Value>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct WorkspaceFolder {
+    /// Local folder, absolute or relative to the workspace file
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
+    /// Remote folders (`vscode-remote://...`) only have a uri
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub uri: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct WorkspaceExtensions {
+    #[serde(default)]
+    pub recommendations: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub unwanted_recommendations: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct WorkspaceLaunch {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+    #[serde(default)]
+    pub configurations: Vec<Value>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub compounds: Vec<Value>,
+    #[serde(flatten)]
+    pub other: Map<String, Value>,
+}
+
+impl WorkspaceFile {
+    /// Parse workspace JSON the way VS Code does, allowing comments and trailing commas
+    pub fn parse(content: &str) -> Result<Self, String> {
+        serde_json::from_str(&strip_jsonc(content))
+            .map_err(|e| format!("Failed to parse workspace file: {}", e))
+    }
+
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let content = fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read workspace file: {}", e))?;
+        Self::parse(&content)
+    }
+
+    /// Load the file of a workspace project. A multi-repo manifest becomes a workspace with
+    /// one folder per repo.
+    pub fn load_project(path: &Path) -> Result<Self, String> {
+        if ManifestFormat::detect(path).is_none() {
+            return Self::load(path);
+        }
+
+        let (_, repos) = load_manifest(path)?;
+        Ok(Self {
+            folders: repos
+                .into_iter()
+                .map(|repo| WorkspaceFolder {
+                    path: Some(repo.path.to_string_lossy().to_string()),
+                    uri: None,
+                    name: Some(repo.name),
+                })
+                .collect(),
+            ..Default::default()
+        })
+    }
+}
+
+impl WorkspaceFolder {
+    /// Local path of the folder; relative paths are resolved against the workspace file's directory
+    pub fn resolve(&self, workspace_path: &Path) -> Option<PathBuf> {
+        let path = self.path.as_deref()?;
+        let path = Path::new(path);
+        if path.is_absolute() {
+            return Some(path.to_path_buf());
+        }
+
+        let joined = workspace_path.parent().unwrap_or(workspace_path).join(path);
+        Some(joined.canonicalize().unwrap_or(joined))
+    }
+}
+
+/// Turn JSONC into plain JSON by dropping `//` and `/* */` comments and trailing commas
+pub fn strip_jsonc(content: &str) -> String {
+    let chars: Vec<char> = content.chars().collect();
+    let mut out = String::with_capacity(content.len());
+    let mut i = 0;
+    let mut in_string = false;
+
+    while i < chars.len() {
+        let c = chars[i];
+
+        if in_string {
+            out.push(c);
+            if c == '\\' && i + 1 < chars.len() {
+                out.push(chars[i + 1]);
+                i += 1;
+            } else if c == '"' {
+                in_string = false;
+            }
+            i += 1;
+            continue;
+        }
+
+        match c {
+            '"' => {
+                in_string = true;
+                out.push(c);
+                i += 1;
+            }
+            '/' if chars.get(i + 1) == Some(&'/') => {
+                while i < chars.len() && chars[i] != '\n' {
+                    i += 1;
+                }
+            }
+            '/' if chars.get(i + 1) == Some(&'*') => {
+                i += 2;
+                while i < chars.len() && !(chars[i] == '*' && chars.get(i + 1) == Some(&'/')) {
+                    i += 1;
+                }
+                i += 2;
+                // Keep tokens on either side of the comment apart
+                out.push(' ');
+            }
+            ',' => {
+                if !next_is_closing(&chars, i + 1) {
+                    out.push(c);
+                }
+                i += 1;
+            }
+            _ => {
+                out.push(c);
+                i += 1;
+            }
+        }
+    }
+
+    out
+}
+
+/// Whether the next significant character after `start` closes an object or array
+fn next_is_closing(chars: &[char], start: usize) -> bool {
+    let mut i = start;
+    while i < chars.len() {
+        match chars[i] {
+            c if c.is_whitespace() => i += 1,
+            '/' if chars.get(i + 1) == Some(&'/') => {
+                while i < chars.len() && chars[i] != '\n' {
+                    i += 1;
+                }
+            }
+            '/' if chars.get(i + 1) == Some(&'*') => {
+                i += 2;
+                while i < chars.len() && !(chars[i] == '*' && chars.get(i + 1) == Some(&'/')) {
+                    i += 1;
+                }
+                i += 2;
+            }
+            '}' | ']' => return true,
+            _ => return false,
+        }
+    }
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_jsonc_workspace() {
+        let content = r#"{
+            // Repositories in this workspace
+            "folders": [
+                { "path": "./api", "name": "API" }, /* the backend */
+                { "path": "https://example.com//not-a-comment" },
+            ],
+            "settings": { "editor.tabSize": 2, },
+            "extensions": { "recommendations": ["rust-lang.rust-analyzer",] },
+            "launch": { "version": "0.2.0", "configurations": [] },
+            "tasks": { "version": "2.0.0" },
+        }"#;
+
+        let workspace = WorkspaceFile::parse(content).unwrap();
+        assert_eq!(workspace.folders.len(), 2);
+        assert_eq!(workspace.folders[0].name.as_deref(), Some("API"));
+        assert_eq!(workspace.folders[1].path.as_deref(), Some("https://example.com//not-a-comment"));
+        assert_eq!(workspace.settings.get("editor.tabSize"), Some(&Value::from(2)));
+        assert_eq!(workspace.extensions.unwrap().recommendations, vec!["rust-lang.rust-analyzer"]);
+        assert_eq!(workspace.launch.unwrap().version.as_deref(), Some("0.2.0"));
+        assert!(workspace.other.contains_key("tasks"));
+    }
+
+    #[test]
+    fn test_strip_jsonc_keeps_strings() {
+        let content = r#"{"a": "x, ]", "b": "/* y */", "c": "\"//\""}"#;
+        assert_eq!(strip_jsonc(content), content);
+    }
+}
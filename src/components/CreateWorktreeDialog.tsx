@@ -27,6 +27,12 @@ export function CreateWorktreeDialog({
   const [isLoading, setIsLoading] = useState(false);
   const [isLoadingBranches, setIsLoadingBranches] = useState(false);
   const [useCustomBranch, setUseCustomBranch] = useState(false);
+  const [baseRef, setBaseRef] = useState('');
+  // Undefined leaves tracking to git, which only tracks remote base branches
+  const [trackUpstream, setTrackUpstream] = useState<boolean | undefined>(undefined);
+  const [seedCaches, setSeedCaches] = useState(false);
+  const [protectedBranches, setProtectedBranches] = useState<string[]>([]);
+  const [sparsePaths, setSparsePaths] = useState('');
 
   useEffect(() => {
     if (isOpen && projectPath) {
@@ -40,9 +46,14 @@ export function CreateWorktreeDialog({
       const branches = await invoke<string[]>('get_available_branches', {
         projectPath: projectPath
       });
+      const config = await invoke<{ protected_branches: string[] }>('get_project_config', {
+        path: projectPath
+      });
       setAvailableBranches(branches);
+      setProtectedBranches(config.protected_branches);
       if (branches.length > 0) {
-        setSelectedBranch(branches[0]); // Default to first branch (main/master)
+        setSelectedBranch(branches[0]); // Default to first branch (protected branches come first)
+        setBaseRef(branches[0]);
         // Auto-suggest worktree name based on branch
         if (!worktreeName) {
           setWorktreeName(branches[0]);
@@ -62,13 +73,19 @@ export function CreateWorktreeDialog({
       return;
     }
 
+    // One directory per line or comma-separated; empty means a full checkout
+    const sparse_paths = sparsePaths.split(/[\n,]/).map((p) => p.trim()).filter(Boolean);
+
     setIsLoading(true);
     try {
       const worktree = await invoke('create_worktree', {
         projectPath: projectPath,
         branch: branchToUse.trim(),
         projectId: projectId,
-        worktreeName: worktreeName.trim()
+        worktreeName: worktreeName.trim(),
+        options: useCustomBranch
+          ? { create_branch: true, base_ref: baseRef.trim() || null, track: trackUpstream, seed_caches: seedCaches, sparse_paths }
+          : { create_branch: false, seed_caches: seedCaches, sparse_paths }
       });
       
       onSuccess(worktree);
@@ -79,6 +96,10 @@ export function CreateWorktreeDialog({
       setCustomBranch('');
       setWorktreeName('');
       setUseCustomBranch(false);
+      setBaseRef('');
+      setTrackUpstream(undefined);
+      setSeedCaches(false);
+      setSparsePaths('');
     } catch (error) {
       console.error('Failed to create worktree:', error);
       // TODO: Show error toast
@@ -92,6 +113,10 @@ export function CreateWorktreeDialog({
     setCustomBranch('');
     setWorktreeName('');
     setUseCustomBranch(false);
+    setBaseRef('');
+    setTrackUpstream(undefined);
+    setSeedCaches(false);
+    setSparsePaths('');
     onClose();
   };
 
@@ -200,6 +225,11 @@ export function CreateWorktreeDialog({
                     ))}
                   </select>
                 )}
+                {protectedBranches.includes(selectedBranch) && (
+                  <p className="text-xs mt-1" style={{ color: 'rgb(var(--color-muted-foreground))' }}>
+                    {selectedBranch} is protected, so the worktree gets a detached HEAD at its latest commit. Use New Branch to work on top of it.
+                  </p>
+                )}
               </div>
             ) : (
               <div className="flex items-center gap-2">
@@ -225,6 +255,38 @@ export function CreateWorktreeDialog({
                 />
               </div>
             )}
+
+            {useCustomBranch && (
+              <div className="mt-3 space-y-2">
+                <label className="text-sm font-medium block">Based on</label>
+                <input
+                  type="text"
+                  list="worktree-base-refs"
+                  value={baseRef}
+                  onChange={(e) => setBaseRef(e.target.value)}
+                  placeholder="Branch, remote branch, tag or commit"
+                  className="w-full p-2 rounded border"
+                  style={{
+                    backgroundColor: 'rgb(var(--color-background))',
+                    borderColor: 'rgb(var(--color-border))',
+                    color: 'rgb(var(--color-foreground))'
+                  }}
+                />
+                <datalist id="worktree-base-refs">
+                  {availableBranches.map((branch) => (
+                    <option key={branch} value={branch} />
+                  ))}
+                </datalist>
+                <label className="flex items-center gap-2 text-sm" style={{ color: 'rgb(var(--color-foreground))' }}>
+                  <input
+                    type="checkbox"
+                    checked={trackUpstream ?? false}
+                    onChange={(e) => setTrackUpstream(e.target.checked)}
+                  />
+                  Track the base branch as upstream
+                </label>
+              </div>
+            )}
           </div>
 
           <div>
@@ -246,6 +308,34 @@ export function CreateWorktreeDialog({
             </p>
           </div>
 
+          <label className="flex items-center gap-2 text-sm" style={{ color: 'rgb(var(--color-foreground))' }}>
+            <input
+              type="checkbox"
+              checked={seedCaches}
+              onChange={(e) => setSeedCaches(e.target.checked)}
+            />
+            Reuse dependencies and build output (node_modules, target) from the main checkout
+          </label>
+
+          <div>
+            <label className="text-sm font-medium mb-2 block">Sparse Checkout (optional)</label>
+            <textarea
+              value={sparsePaths}
+              onChange={(e) => setSparsePaths(e.target.value)}
+              placeholder={'packages/api\npackages/shared'}
+              rows={2}
+              className="w-full p-2 rounded border font-mono text-sm"
+              style={{
+                backgroundColor: 'rgb(var(--color-background))',
+                borderColor: 'rgb(var(--color-border))',
+                color: 'rgb(var(--color-foreground))'
+              }}
+            />
+            <p className="text-xs mt-1" style={{ color: 'rgb(var(--color-muted-foreground))' }}>
+              Only check out these directories, plus the files at the repository root. Leave empty for a full checkout.
+            </p>
+          </div>
+
           <div className="text-xs p-3 rounded" style={{ 
             backgroundColor: 'rgb(var(--color-muted))',
             color: 'rgb(var(--color-muted-foreground))'
@@ -130,15 +130,18 @@ export function ProjectForm({ mode }: ProjectFormProps) {
     }
     
     try {
-      const projectWorktrees = await invoke<any[]>('list_worktrees', {
-        projectPath: selectedProject.path
+      const allWorktrees = await invoke<any[]>('list_worktrees', {
+        projectPath: selectedProject.path,
+        projectId: selectedProject.id
       });
+      // The bare repository itself has no working tree to open
+      const projectWorktrees = allWorktrees.filter(wt => !wt.is_bare);
       setWorktrees(projectWorktrees);
       
       // Update the project store with the loaded worktrees
       const formattedWorktrees = projectWorktrees.map(wt => ({
         id: wt.id,
-        branch: wt.branch,
+        branch: wt.is_detached && wt.head ? `detached@${wt.head.slice(0, 7)}` : wt.branch,
         path: wt.path,
         createdAt: new Date(wt.created_at)
       }));
@@ -169,14 +172,39 @@ export function ProjectForm({ mode }: ProjectFormProps) {
   };
 
   const handleDeleteWorktree = async (worktree: any) => {
-    if (!window.confirm(`Are you sure you want to delete the worktree for "${worktree.branch}"?`)) {
-      return;
-    }
-
     try {
+      // Warn about work that would be lost before removing anything
+      const check = await invoke<{
+        uncommitted: string[];
+        untracked: string[];
+        stashes: string[];
+        unpushed_commits: string[];
+        safe: boolean;
+      }>('check_worktree_removal', { worktreePath: worktree.path });
+
+      if (check.safe) {
+        if (!window.confirm(`Are you sure you want to delete the worktree for "${worktree.branch}"?`)) {
+          return;
+        }
+      } else {
+        const details = [
+          check.uncommitted.length && `${check.uncommitted.length} uncommitted file(s)`,
+          check.untracked.length && `${check.untracked.length} untracked file(s)`,
+          check.stashes.length && `${check.stashes.length} stash(es)`,
+          check.unpushed_commits.length && `${check.unpushed_commits.length} unpushed commit(s)`,
+        ].filter(Boolean).join('\n');
+        if (!window.confirm(
+          `The worktree for "${worktree.branch}" has:\n${details}\n\nDelete it anyway? A backup of its current state will be kept.`
+        )) {
+          return;
+        }
+      }
+
       await invoke('remove_worktree', {
         projectPath: selectedProject?.path,
-        worktreePath: worktree.path
+        worktreePath: worktree.path,
+        projectId: selectedProject?.id,
+        options: check.safe ? null : { confirmed: true, backup: true }
       });
       
       setWorktrees(prev => prev.filter(w => w.id !== worktree.id));
@@ -228,14 +256,16 @@ export function ProjectForm({ mode }: ProjectFormProps) {
         // Load worktrees for the newly created project (to discover default worktree)
         if (createdProject.project_type === 'repository') {
           try {
-            const projectWorktrees = await invoke<any[]>('list_worktrees', {
-              projectPath: createdProject.path
+            const allWorktrees = await invoke<any[]>('list_worktrees', {
+              projectPath: createdProject.path,
+              projectId: createdProject.id
             });
+            const projectWorktrees = allWorktrees.filter(wt => !wt.is_bare);
             
             // Format and update project with discovered worktrees
             const formattedWorktrees = projectWorktrees.map(wt => ({
               id: wt.id,
-              branch: wt.branch,
+              branch: wt.is_detached && wt.head ? `detached@${wt.head.slice(0, 7)}` : wt.branch,
               path: wt.path,
               createdAt: new Date(wt.created_at)
             }));
@@ -306,8 +336,14 @@ export function ProjectForm({ mode }: ProjectFormProps) {
     }
   };
 
-  const handleDeleteProject = () => {
+  const handleDeleteProject = async () => {
     if (selectedProject && window.confirm(`Are you sure you want to remove "${selectedProject.name}" from the project list?`)) {
+      try {
+        await invoke('remove_project', { id: selectedProject.id });
+      } catch (error) {
+        // Projects added before the backend registry existed are only known to the frontend
+        console.warn('Failed to remove project from registry:', error);
+      }
       removeProject(selectedProject.id);
     }
   };
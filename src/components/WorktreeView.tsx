@@ -3,6 +3,7 @@ import { invoke } from '@tauri-apps/api/core';
 import { Plus, X, Terminal as TerminalIcon } from 'lucide-react';
 import { useProjectStore } from '@/stores/projectStore';
 import { useTerminalStore } from '@/stores/terminalStore';
+import { useBootstrapStore } from '@/stores/bootstrapStore';
 import { Terminal } from './Terminal';
 
 export function WorktreeView() {
@@ -38,6 +39,19 @@ export function WorktreeView() {
   const activeTerminal = worktree ? getActiveTerminalForWorktree(worktree.id) : undefined;
   const activeTerminalId = activeTerminal?.id || null;
 
+  // Bootstrap pipeline of the selected worktree, while it runs, after it failed or while its setup awaits trust
+  const bootstrapRun = useBootstrapStore(state => worktree ? state.runs[worktree.path] : undefined);
+  const dismissBootstrap = useBootstrapStore(state => state.dismiss);
+  const currentBootstrapStep = bootstrapRun?.steps[bootstrapRun.steps.length - 1];
+
+  // Terminals and env from .manymany.toml waiting for the user's approval, with the commands held back
+  const [repoTrust, setRepoTrust] = useState<{
+    worktreeId: string;
+    terminals: { name: string; command: string }[];
+    env: Record<string, string>;
+    pending: { backendTerminalId: string; command: string }[];
+  } | null>(null);
+
   // Local state for terminal creation loading
   const [isCreatingTerminal, setIsCreatingTerminal] = useState(false);
   
@@ -90,9 +104,36 @@ export function WorktreeView() {
     
     // Create default terminals
     const createDefaultTerminals = async () => {
-      if (defaultTerminals.length === 0) return;
+      // Terminals customized for the project beat the ones from .manymany.toml and user settings
+      let terminalsToCreate: { name: string; command: string }[] = defaultTerminals;
+      let config: {
+        terminals: { name: string; command: string }[];
+        terminals_trusted: boolean;
+        untrusted_env: Record<string, string>;
+      } | null = null;
+      try {
+        config = await invoke('get_project_config', { path: worktree.path });
+      } catch (error) {
+        console.error('Failed to load terminal presets:', error);
+      }
+      if (!project?.defaultTerminals && config && config.terminals.length > 0) {
+        terminalsToCreate = config.terminals;
+      }
+      // Commands of unapproved repo terminals are shown in a banner instead of being run
+      const holdCommands = !!config && terminalsToCreate === config.terminals && !config.terminals_trusted;
+      const pending: { backendTerminalId: string; command: string }[] = [];
       
-      for (const defaultTerminal of defaultTerminals) {
+      if (config && (holdCommands || Object.keys(config.untrusted_env).length > 0)) {
+        setRepoTrust({
+          worktreeId: worktree.id,
+          terminals: holdCommands ? config.terminals : [],
+          env: config.untrusted_env,
+          pending,
+        });
+      }
+      if (terminalsToCreate.length === 0) return;
+      
+      for (const defaultTerminal of terminalsToCreate) {
         try {
           // Double-check that we don't have a terminal with this name already
           const existingTerminals = getTerminalsForWorktree(worktree.id);
@@ -107,7 +148,7 @@ export function WorktreeView() {
             name: defaultTerminal.name,
             worktreeId: worktree.id,
             workingDirectory: worktree.path,
-            autoCommand: defaultTerminal.command.trim() || undefined,
+            autoCommand: holdCommands ? undefined : defaultTerminal.command.trim() || undefined,
           });
           
           // Create backend terminal
@@ -121,6 +162,9 @@ export function WorktreeView() {
           
           // Update terminal with backend ID
           setBackendTerminalId(newTerminal.id, backendTerminalId);
+          if (holdCommands && defaultTerminal.command.trim()) {
+            pending.push({ backendTerminalId, command: defaultTerminal.command.trim() });
+          }
           
         } catch (error) {
           console.error(`Failed to create default terminal "${defaultTerminal.name}":`, error);
@@ -249,6 +293,25 @@ export function WorktreeView() {
     renameTerminal(terminalId, newName);
   };
 
+  // Approve the repo's terminals and env, then run the commands that were held back.
+  // Terminals that are already open keep the environment they started with.
+  const trustRepoConfig = async () => {
+    if (!repoTrust) return;
+    try {
+      await invoke('trust_repo_config', {
+        projectPath: project?.path,
+        terminals: repoTrust.terminals.length > 0 ? repoTrust.terminals : null,
+        env: Object.keys(repoTrust.env).length > 0 ? repoTrust.env : null,
+      });
+      for (const { backendTerminalId, command } of repoTrust.pending) {
+        await invoke('terminal_input', { terminalId: backendTerminalId, data: command + '\n' });
+      }
+      setRepoTrust(null);
+    } catch (error) {
+      console.error('Failed to trust .manymany.toml:', error);
+    }
+  };
+
   const setActiveTerminalId = async (terminalId: string | null) => {
     if (!worktree) return;
     
@@ -268,6 +331,77 @@ export function WorktreeView() {
     <div className="flex-1 flex flex-col h-full" style={{ minHeight: 0 }}>
       {/* Terminal Area - ALWAYS rendered to prevent unmounting */}
       <div className="flex-1 flex flex-col" style={{ minHeight: 0 }}>
+        {worktree && repoTrust && repoTrust.worktreeId === worktree.id && (
+          <div className="px-4 py-2 border-b text-sm flex items-center gap-2 flex-shrink-0"
+               style={{ borderColor: 'rgb(var(--color-border))', color: 'rgb(var(--color-muted-foreground))' }}>
+            <span
+              className="flex-1 truncate"
+              title={[
+                ...repoTrust.terminals.filter(t => t.command.trim()).map(t => `${t.name}: ${t.command}`),
+                ...Object.entries(repoTrust.env).map(([key, value]) => `${key}=${value}`),
+              ].join('\n')}
+            >
+              {[
+                repoTrust.terminals.some(t => t.command.trim()) &&
+                  `.manymany.toml terminals want to run: ${repoTrust.terminals.map(t => t.command.trim()).filter(Boolean).join(', ')}`,
+                Object.keys(repoTrust.env).length > 0 &&
+                  `.manymany.toml wants to set: ${Object.keys(repoTrust.env).join(', ')}`,
+              ].filter(Boolean).join(' · ')}
+            </span>
+            <button className="text-xs underline" onClick={trustRepoConfig}>
+              Trust and run
+            </button>
+            <button className="p-0.5" onClick={() => setRepoTrust(null)}>
+              <X className="w-3 h-3" />
+            </button>
+          </div>
+        )}
+        {worktree && bootstrapRun && !bootstrapRun.running && bootstrapRun.success !== false && bootstrapRun.untrustedSetup.length > 0 && (
+          <div className="px-4 py-2 border-b text-sm flex items-center gap-2 flex-shrink-0"
+               style={{ borderColor: 'rgb(var(--color-border))', color: 'rgb(var(--color-muted-foreground))' }}>
+            <span className="flex-1 truncate" title={bootstrapRun.untrustedSetup.join('\n')}>
+              {`.manymany.toml wants to run: ${bootstrapRun.untrustedSetup.join(' && ')}`}
+            </span>
+            <button
+              className="text-xs underline"
+              onClick={() => invoke('trust_setup_commands', { projectPath: project?.path, commands: bootstrapRun.untrustedSetup })
+                .then(() => invoke('bootstrap_worktree', { projectPath: project?.path, worktreePath: worktree.path }))
+                .catch(error => console.error('Failed to run worktree setup:', error))}
+            >
+              Trust and run
+            </button>
+            <button className="p-0.5" onClick={() => dismissBootstrap(worktree.path)}>
+              <X className="w-3 h-3" />
+            </button>
+          </div>
+        )}
+        {worktree && bootstrapRun && (bootstrapRun.running || bootstrapRun.success === false) && (
+          <div className="px-4 py-2 border-b text-sm flex items-center gap-2 flex-shrink-0"
+               style={{
+                 borderColor: 'rgb(var(--color-border))',
+                 color: bootstrapRun.running ? 'rgb(var(--color-muted-foreground))' : 'rgb(var(--color-destructive))'
+               }}>
+            <span className="flex-1 truncate" title={currentBootstrapStep?.output.join('\n')}>
+              {bootstrapRun.running
+                ? `Setting up worktree: ${currentBootstrapStep?.step ?? 'starting'}`
+                : `Worktree setup failed at: ${bootstrapRun.steps.find(s => s.success === false)?.step ?? 'unknown step'}`}
+            </span>
+            {!bootstrapRun.running && (
+              <>
+                <button
+                  className="text-xs underline"
+                  onClick={() => invoke('bootstrap_worktree', { projectPath: project?.path, worktreePath: worktree.path })
+                    .catch(error => console.error('Failed to re-run worktree setup:', error))}
+                >
+                  Retry
+                </button>
+                <button className="p-0.5" onClick={() => dismissBootstrap(worktree.path)}>
+                  <X className="w-3 h-3" />
+                </button>
+              </>
+            )}
+          </div>
+        )}
         {/* Terminal Tabs - show if current worktree has terminals */}
         {terminals.length > 0 && (
           <div className="h-9 flex items-center gap-1 px-4 border-b overflow-x-auto flex-shrink-0" 
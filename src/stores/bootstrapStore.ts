@@ -0,0 +1,96 @@
+import { create } from 'zustand';
+import { listen } from '@tauri-apps/api/event';
+
+// Mirrors BootstrapEvent in src-tauri/src/commands/bootstrap.rs
+interface BootstrapEvent {
+  worktree_path: string;
+  kind: 'step_started' | 'output' | 'step_finished' | 'setup_untrusted' | 'finished';
+  step: string | null;
+  output: string | null;
+  success: boolean | null;
+}
+
+export interface BootstrapStep {
+  step: string;
+  success: boolean | null; // null while running
+  output: string[];
+}
+
+export interface BootstrapRun {
+  running: boolean;
+  success: boolean | null;
+  steps: BootstrapStep[];
+  // Setup commands waiting for the user to trust them
+  untrustedSetup: string[];
+}
+
+interface BootstrapStore {
+  // Latest bootstrap run per worktree path
+  runs: Record<string, BootstrapRun>;
+
+  handleEvent: (event: BootstrapEvent) => void;
+  dismiss: (worktreePath: string) => void;
+}
+
+export const useBootstrapStore = create<BootstrapStore>()((set) => {
+  // Listen from the start so events of a worktree being created aren't missed
+  listen<BootstrapEvent>('worktree-bootstrap', (event) => {
+    useBootstrapStore.getState().handleEvent(event.payload);
+  }).catch((error) => {
+    console.error('Failed to listen for bootstrap events:', error);
+  });
+
+  return {
+    runs: {},
+
+    handleEvent: (event) => {
+      set((state) => {
+        const existing = state.runs[event.worktree_path];
+        // A new run starts with its first step
+        const run: BootstrapRun = existing && existing.running
+          ? { ...existing, steps: [...existing.steps], untrustedSetup: [...existing.untrustedSetup] }
+          : { running: true, success: null, steps: [], untrustedSetup: [] };
+
+        const last = run.steps[run.steps.length - 1];
+        switch (event.kind) {
+          case 'step_started':
+            run.steps.push({ step: event.step ?? '', success: null, output: [] });
+            break;
+          case 'output':
+            if (last && event.output !== null) {
+              run.steps[run.steps.length - 1] = { ...last, output: [...last.output, event.output] };
+            }
+            break;
+          case 'step_finished':
+            if (last) {
+              run.steps[run.steps.length - 1] = {
+                ...last,
+                success: event.success,
+                output: event.output !== null ? [...last.output, event.output] : last.output,
+              };
+            }
+            break;
+          case 'setup_untrusted':
+            if (event.step !== null) {
+              run.untrustedSetup.push(event.step);
+            }
+            break;
+          case 'finished':
+            run.running = false;
+            run.success = event.success;
+            break;
+        }
+
+        return { runs: { ...state.runs, [event.worktree_path]: run } };
+      });
+    },
+
+    dismiss: (worktreePath) => {
+      set((state) => {
+        const runs = { ...state.runs };
+        delete runs[worktreePath];
+        return { runs };
+      });
+    },
+  };
+});